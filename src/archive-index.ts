@@ -0,0 +1,120 @@
+import { FindOpts, IFileMedia } from "./interfaces.js";
+import { InnerFile } from "./inner-file.js";
+import { IFileHeader } from "./parsing/file-header-parser.js";
+import { RarError } from "./rar-error.js";
+import { RarFileChunk } from "./rar-file-chunk.js";
+
+const INDEX_VERSION = 1;
+
+type IndexedFileHeader = Omit<IFileHeader, "nameBytes"> & {
+  // base64
+  nameBytes: string;
+};
+
+export interface IArchiveIndex {
+  version: number;
+  volumes: { name: string; length: number }[];
+  entries: {
+    name: string;
+    fileHead?: IndexedFileHeader;
+    // [volumeIndex, startOffset, endOffset] with an inclusive end
+    chunks: [number, number, number][];
+    // only set for entries parsed with volumes missing
+    incomplete?: boolean;
+    missingVolumes?: string[];
+    comment?: string;
+  }[];
+}
+
+export function createIndex(
+  volumes: IFileMedia[],
+  innerFiles: InnerFile[]
+): IArchiveIndex {
+  return {
+    version: INDEX_VERSION,
+    volumes: volumes.map(({ name, length }) => ({ name, length })),
+    entries: innerFiles.map((innerFile) => ({
+      name: innerFile.name,
+      ...(innerFile.fileHead && {
+        fileHead: {
+          ...innerFile.fileHead,
+          nameBytes: innerFile.fileHead.nameBytes.toString("base64"),
+        },
+      }),
+      chunks: innerFile.chunkMap.map(({ chunk }) => [
+        chunk.volumeIndex,
+        chunk.startOffset,
+        chunk.endOffset,
+      ]),
+      ...(innerFile.comment !== undefined && { comment: innerFile.comment }),
+      ...(innerFile.incomplete && {
+        incomplete: true,
+        missingVolumes: innerFile.missingVolumes,
+      }),
+    })),
+  };
+}
+
+export function restoreIndex(
+  index: IArchiveIndex,
+  volumes: IFileMedia[],
+  opts: FindOpts = {}
+) {
+  const mismatch = (message: string): never => {
+    throw new RarError("INDEX_MISMATCH", `Archive index does not match: ${message}`);
+  };
+  if (index.version !== INDEX_VERSION) {
+    mismatch(`unknown version ${index.version}`);
+  }
+  if (index.volumes.length !== volumes.length) {
+    mismatch(`expected ${index.volumes.length} volumes, got ${volumes.length}`);
+  }
+  index.volumes.forEach(({ name, length }, volumeIndex) => {
+    const volume = volumes[volumeIndex]!;
+    if (volume.name !== name || volume.length !== length) {
+      mismatch(
+        `expected volume ${volumeIndex} to be ${name} (${length} bytes), got ${volume.name} (${volume.length} bytes)`
+      );
+    }
+  });
+  // a stale or corrupted index would otherwise only fail once read
+  const chunkOf = (
+    name: string,
+    [volumeIndex, start, end]: [number, number, number]
+  ) => {
+    const volume = volumes[volumeIndex];
+    if (!volume) {
+      return mismatch(
+        `${name} refers to volume ${volumeIndex}, which is not listed`
+      );
+    }
+    // an empty chunk ends right before it starts
+    if (!(start >= 0 && end >= start - 1 && end < volume.length)) {
+      return mismatch(
+        `${name} refers to bytes ${start}-${end} of ${volume.name}, which has ${volume.length} bytes`
+      );
+    }
+    return new RarFileChunk(volume, start, end, volumeIndex);
+  };
+  return index.entries.map(
+    ({ name, fileHead, chunks, incomplete, missingVolumes, comment }) => {
+      const innerFile = new InnerFile(
+        name,
+        chunks.map((chunk) => chunkOf(name, chunk)),
+        fileHead && {
+          ...fileHead,
+          nameBytes: Buffer.from(fileHead.nameBytes, "base64"),
+        },
+        opts
+      );
+      if (incomplete) {
+        innerFile.incomplete = true;
+        innerFile.missingVolumes = missingVolumes ?? [];
+      }
+      if (comment !== undefined) {
+        innerFile.comment = comment;
+      }
+      return innerFile;
+    }
+  );
+}
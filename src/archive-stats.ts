@@ -0,0 +1,96 @@
+import { CompressionMethod, describeCompression } from "./compression-info.js";
+import { visitHeaders } from "./header-visitor.js";
+import { IFileMedia } from "./interfaces.js";
+
+export interface ISizeStats {
+  count: number;
+  packedSize: number;
+  unpackedSize: number;
+}
+
+export interface IEntrySize {
+  name: string;
+  packedSize: number;
+  unpackedSize: number;
+}
+
+export interface IArchiveStats extends ISizeStats {
+  directoryCount: number;
+  // packed size over unpacked size, 1 for stored data, 0 without data
+  ratio: number;
+  methods: Partial<Record<CompressionMethod, ISizeStats>>;
+  encryptedCount: number;
+  solidCount: number;
+  // by unpacked size, largest first
+  largest: IEntrySize[];
+}
+
+// Totals over the file headers of every volume, compressed and encrypted
+// entries included. Split entries count once with the packed size of all
+// their parts, directories only count towards directoryCount.
+export async function archiveStats(
+  volumes: IFileMedia[],
+  maxScanBytes: number,
+  largestCount: number
+): Promise<IArchiveStats> {
+  const stats: IArchiveStats = {
+    count: 0,
+    packedSize: 0,
+    unpackedSize: 0,
+    directoryCount: 0,
+    ratio: 0,
+    methods: {},
+    encryptedCount: 0,
+    solidCount: 0,
+    largest: [],
+  };
+  const entries = new Map<string, IEntrySize & { method: ISizeStats }>();
+  await visitHeaders(
+    volumes,
+    {
+      file(header) {
+        if (header.isDirectory) {
+          stats.directoryCount++;
+          return;
+        }
+        const previous = entries.get(header.name);
+        if (header.continuesFromPrevious && previous) {
+          previous.packedSize += header.size;
+          previous.method.packedSize += header.size;
+          stats.packedSize += header.size;
+          return;
+        }
+        const compression = describeCompression(header);
+        const method = (stats.methods[compression.method] ??= {
+          count: 0,
+          packedSize: 0,
+          unpackedSize: 0,
+        });
+        for (const totals of [stats, method]) {
+          totals.count++;
+          totals.packedSize += header.size;
+          totals.unpackedSize += header.unpackedSize;
+        }
+        stats.encryptedCount += compression.isEncrypted ? 1 : 0;
+        stats.solidCount += compression.isSolid ? 1 : 0;
+        entries.set(header.name, {
+          name: header.name,
+          packedSize: header.size,
+          unpackedSize: header.unpackedSize,
+          method,
+        });
+      },
+    },
+    maxScanBytes
+  );
+  stats.ratio = stats.unpackedSize ? stats.packedSize / stats.unpackedSize : 0;
+  stats.largest = [...entries.values()]
+    .sort((a, b) => b.unpackedSize - a.unpackedSize)
+    .slice(0, largestCount)
+    .map(({ name, packedSize, unpackedSize }) => ({
+      name,
+      packedSize,
+      unpackedSize,
+    }));
+  return stats;
+}
@@ -0,0 +1,12 @@
+#!/usr/bin/env node
+import { run } from "./cli.js";
+
+run(process.argv.slice(2)).then(
+  (code) => {
+    process.exitCode = code;
+  },
+  (error: Error) => {
+    process.stderr.write(`rar-stream: ${error.message}\n`);
+    process.exitCode = 1;
+  }
+);
@@ -0,0 +1,37 @@
+import { expect, test } from "vitest";
+import path from "path";
+import fs from "fs";
+
+import { BlobFileMedia } from "./blob-file-media.js";
+import { RarFilesPackage } from "./rar-files-package.js";
+import { streamToBuffer } from "./stream-utils.js";
+
+const fixturePath = path.resolve(__dirname, "./__fixtures__");
+
+test("BlobFileMedia should have the size of its blob", () => {
+  const media = new BlobFileMedia(new Blob(["0123456789"]), "digits");
+  expect(media.name).toBe("digits");
+  expect(media.length).toBe(10);
+});
+
+test("BlobFileMedia#createReadStream should return an inclusive byte range", async () => {
+  const media = new BlobFileMedia(new Blob(["0123456789"]), "digits");
+  const buffer = await streamToBuffer(
+    await media.createReadStream({ start: 2, end: 5 })
+  );
+  expect(buffer.toString()).toBe("2345");
+});
+
+test("rar package can parse and read blob volumes", async () => {
+  const medias = ["multi.rar", "multi.r00", "multi.r01"].map(
+    (name) =>
+      new BlobFileMedia(
+        new Blob([fs.readFileSync(path.join(fixturePath, "multi", name))]),
+        name
+      )
+  );
+  const [file] = await new RarFilesPackage(medias).parse();
+  expect(await file!.readToEnd()).toEqual(
+    fs.readFileSync(path.join(fixturePath, "multi/multi.txt"))
+  );
+});
@@ -0,0 +1,17 @@
+import { Readable } from "stream";
+import type { ReadableStream as WebReadableStream } from "stream/web";
+import { IFileMedia, IReadInterval } from "./interfaces.js";
+
+export class BlobFileMedia implements IFileMedia {
+  length: number;
+  constructor(private blob: Blob, public name: string) {
+    this.length = blob.size;
+  }
+  createReadStream(interval: IReadInterval) {
+    const { start, end } = interval;
+    const slice = this.blob.slice(start, end + 1);
+    return Promise.resolve(
+      Readable.fromWeb(slice.stream() as WebReadableStream)
+    );
+  }
+}
@@ -0,0 +1,182 @@
+import { expect, test } from "vitest";
+import path from "path";
+import fs from "fs";
+
+import { CachedFileMedia } from "./cached-file-media.js";
+import { IFileMedia, IReadInterval } from "./interfaces.js";
+import { LocalFileMedia } from "./local-file-media.js";
+import { MemoryFileMedia } from "./memory-file-media.js";
+import { mockRarArchive } from "./parsing/__mocks__/mock-rar-archive.js";
+import { RarFilesPackage } from "./rar-files-package.js";
+import { streamToBuffer } from "./stream-utils.js";
+
+const fixturePath = path.resolve(__dirname, "./__fixtures__");
+const digits = Buffer.from("0123456789abcdefghij");
+
+class CountingFileMedia implements IFileMedia {
+  reads: IReadInterval[] = [];
+  failures = 0;
+  constructor(private media: IFileMedia) {}
+  get name() {
+    return this.media.name;
+  }
+  get length() {
+    return this.media.length;
+  }
+  createReadStream(interval: IReadInterval) {
+    this.reads.push(interval);
+    if (this.failures > 0) {
+      this.failures--;
+      return Promise.reject(new Error("read failed"));
+    }
+    return this.media.createReadStream(interval);
+  }
+}
+
+const read = async (media: IFileMedia, start: number, end: number) =>
+  (await streamToBuffer(await media.createReadStream({ start, end }))).toString();
+
+test("CachedFileMedia#createReadStream should return an inclusive byte range", async () => {
+  const media = new CachedFileMedia(new MemoryFileMedia(digits, "digits"), {
+    blockSize: 4,
+  });
+  expect(await read(media, 0, 19)).toBe(digits.toString());
+  expect(await read(media, 3, 9)).toBe("3456789");
+  expect(await read(media, 18, 40)).toBe("ij");
+});
+
+test("CachedFileMedia should only fetch each block once", async () => {
+  const upstream = new CountingFileMedia(new MemoryFileMedia(digits, "digits"));
+  const media = new CachedFileMedia(upstream, { blockSize: 4, readAhead: 0 });
+  await read(media, 1, 2);
+  await read(media, 2, 6);
+  await read(media, 0, 7);
+  expect(upstream.reads).toEqual([
+    { start: 0, end: 3 },
+    { start: 4, end: 7 },
+  ]);
+});
+
+test("CachedFileMedia should read ahead on sequential access", async () => {
+  const upstream = new CountingFileMedia(new MemoryFileMedia(digits, "digits"));
+  const media = new CachedFileMedia(upstream, { blockSize: 4, readAhead: 2 });
+  await read(media, 0, 1);
+  expect(upstream.reads.map((r) => r.start).sort((a, b) => a - b)).toEqual([
+    0, 4, 8,
+  ]);
+  await read(media, 4, 9);
+  expect(upstream.reads.map((r) => r.start).sort((a, b) => a - b)).toEqual([
+    0, 4, 8, 12, 16,
+  ]);
+});
+
+test("CachedFileMedia should evict the least recently used block", async () => {
+  const upstream = new CountingFileMedia(new MemoryFileMedia(digits, "digits"));
+  const media = new CachedFileMedia(upstream, {
+    blockSize: 4,
+    capacity: 2,
+    readAhead: 0,
+  });
+  await read(media, 0, 0);
+  await read(media, 4, 4);
+  await read(media, 0, 0);
+  await read(media, 8, 8);
+  await read(media, 0, 0);
+  await read(media, 4, 4);
+  expect(upstream.reads.map((r) => r.start)).toEqual([0, 4, 8, 4]);
+});
+
+test("CachedFileMedia should not cache failed reads", async () => {
+  const upstream = new CountingFileMedia(new MemoryFileMedia(digits, "digits"));
+  const media = new CachedFileMedia(upstream, { blockSize: 4, readAhead: 0 });
+  upstream.failures = 1;
+  await expect(read(media, 0, 3)).rejects.toThrow("read failed");
+  expect(await read(media, 0, 3)).toBe("0123");
+});
+
+test("rar package parses with fewer upstream reads through CachedFileMedia", async () => {
+  const volumes = ["multi.rar", "multi.r00", "multi.r01"].map(
+    (name) => new LocalFileMedia(path.join(fixturePath, "multi", name))
+  );
+  const direct = volumes.map((v) => new CountingFileMedia(v));
+  const cached = volumes.map((v) => new CountingFileMedia(v));
+
+  await new RarFilesPackage(direct).parse();
+  const [file] = await new RarFilesPackage(
+    cached.map((c) => new CachedFileMedia(c))
+  ).parse();
+
+  const count = (medias: CountingFileMedia[]) =>
+    medias.reduce((n, m) => n + m.reads.length, 0);
+  expect(count(cached)).toBeLessThan(count(direct));
+  expect(await file!.readToEnd()).toEqual(
+    fs.readFileSync(path.join(fixturePath, "multi/multi.txt"))
+  );
+});
+
+test("CachedFileMedia#prefetch should load blocks ahead of a read", async () => {
+  const upstream = new CountingFileMedia(new MemoryFileMedia(digits, "digits"));
+  const media = new CachedFileMedia(upstream, { blockSize: 4, readAhead: 0 });
+  await media.prefetch({ start: 9, end: 14 });
+  expect(upstream.reads.map((r) => r.start)).toEqual([8, 12]);
+  expect(await read(media, 10, 13)).toBe("abcd");
+  expect(upstream.reads.length).toBe(2);
+});
+
+test("inner files prefetch the volume ranges they need", async () => {
+  const volumes = ["multi.rar", "multi.r00", "multi.r01"].map(
+    (name) =>
+      new CountingFileMedia(
+        new LocalFileMedia(path.join(fixturePath, "multi", name))
+      )
+  );
+  const [file] = await new RarFilesPackage(
+    volumes.map((v) => new CachedFileMedia(v, { blockSize: 16, readAhead: 0 }))
+  ).parse();
+  for (const volume of volumes) {
+    volume.reads = [];
+  }
+  // the middle of the file lies in the middle of the second volume
+  const start = Math.floor(file!.length / 2);
+  const interval = { start, end: start + 10 };
+  await file!.prefetch(interval);
+  const reads = volumes.map((v) => v.reads.length);
+  expect(reads[0]).toBe(0);
+  expect(reads[1]).toBeGreaterThan(0);
+  expect(reads[2]).toBe(0);
+  await streamToBuffer(await file!.createReadStream(interval));
+  expect(volumes.map((v) => v.reads.length)).toEqual(reads);
+});
+
+test("CachedFileMedia should read ahead for interleaved sequential readers", async () => {
+  const upstream = new CountingFileMedia(new MemoryFileMedia(digits, "digits"));
+  const media = new CachedFileMedia(upstream, { blockSize: 2, readAhead: 1 });
+  await read(media, 0, 1);
+  await read(media, 10, 11);
+  await read(media, 2, 3);
+  await read(media, 12, 13);
+  const starts = () => upstream.reads.map((r) => r.start).sort((a, b) => a - b);
+  // the second reader starts somewhere new, then both continue in order
+  expect(starts()).toEqual([0, 2, 4, 10, 12, 14]);
+});
+
+test("shared inner files serve concurrent readers from one cache", async () => {
+  const data = Buffer.alloc(256 * 1024, "abcdefgh");
+  const volume = new CountingFileMedia(
+    new MemoryFileMedia(
+      mockRarArchive([{ name: "episode.mkv", data }]),
+      "episode.rar"
+    )
+  );
+  const [file] = await new RarFilesPackage([volume]).parse();
+  volume.reads = [];
+  const session = file!.share({ blockSize: 64 * 1024, readAhead: 0 });
+  expect(file!.share()).toBe(session);
+  const [first, second] = await Promise.all([
+    read(session, 0, 100 * 1024),
+    read(session, 50 * 1024, 150 * 1024),
+  ]);
+  expect(first).toBe(data.subarray(0, 100 * 1024 + 1).toString());
+  expect(second).toBe(data.subarray(50 * 1024, 150 * 1024 + 1).toString());
+  expect(volume.reads.length).toBe(3);
+});
@@ -0,0 +1,121 @@
+import { Readable } from "stream";
+import { IFileMedia, IReadInterval } from "./interfaces.js";
+import { Metrics } from "./metrics.js";
+import { streamToBuffer } from "./stream-utils.js";
+
+const MAX_CURSORS = 8;
+
+export interface CachedFileMediaOptions {
+  blockSize?: number;
+  capacity?: number;
+  readAhead?: number;
+  metrics?: Metrics;
+}
+
+export class CachedFileMedia implements IFileMedia {
+  name: string;
+  length: number;
+  private blockSize: number;
+  private capacity: number;
+  private readAhead: number;
+  private metrics: Metrics | undefined;
+  // Map keeps insertion order, so the first key is the least recently used
+  private blocks = new Map<number, Promise<Buffer>>();
+  // where recent sequential readers will continue, several readers can
+  // share one cache
+  private cursors: number[] = [0];
+  constructor(private media: IFileMedia, opts: CachedFileMediaOptions = {}) {
+    this.name = media.name;
+    this.length = media.length;
+    this.blockSize = opts.blockSize ?? 64 * 1024;
+    this.capacity = Math.max(1, opts.capacity ?? 64);
+    this.readAhead = opts.readAhead ?? 2;
+    this.metrics = opts.metrics;
+  }
+  createReadStream(interval: IReadInterval) {
+    const start = interval.start;
+    const end = Math.min(interval.end, this.length - 1);
+    const firstBlock = Math.floor(start / this.blockSize);
+    const lastBlock = Math.floor(end / this.blockSize);
+
+    const cursor = this.cursors.findIndex(
+      (next) => firstBlock === next || firstBlock === next - 1
+    );
+    if (cursor !== -1) {
+      for (let i = 1; i <= this.readAhead; i++) {
+        this.prefetchBlock(lastBlock + i);
+      }
+      this.cursors.splice(cursor, 1);
+    } else if (this.cursors.length >= MAX_CURSORS) {
+      this.cursors.shift();
+    }
+    this.cursors.push(lastBlock + 1);
+
+    return Promise.resolve(
+      Readable.from(this.readBlocks(start, end), { objectMode: false })
+    );
+  }
+  private async *readBlocks(start: number, end: number) {
+    const lastBlock = Math.floor(end / this.blockSize);
+    for (let index = Math.floor(start / this.blockSize); index <= lastBlock; index++) {
+      this.metrics?.addCacheLookup(this.blocks.has(index));
+      const block = await this.getBlock(index);
+      const offset = index * this.blockSize;
+      yield block.subarray(Math.max(start - offset, 0), end - offset + 1);
+    }
+  }
+  private getBlock(index: number) {
+    let block = this.blocks.get(index);
+    if (block) {
+      this.blocks.delete(index);
+      this.blocks.set(index, block);
+    } else {
+      block = this.fetchBlock(index);
+      this.blocks.set(index, block);
+      this.evict();
+    }
+    return block;
+  }
+  // Loads the blocks of the interval ahead of a read, e.g. where a player is
+  // about to seek to. Blocks beyond the capacity push out older ones.
+  async prefetch(interval: IReadInterval) {
+    const end = Math.min(interval.end, this.length - 1);
+    const blocks: Promise<Buffer>[] = [];
+    for (
+      let index = Math.floor(interval.start / this.blockSize);
+      index <= Math.floor(end / this.blockSize);
+      index++
+    ) {
+      blocks.push(this.getBlock(index));
+    }
+    await Promise.all(blocks);
+  }
+  private prefetchBlock(index: number) {
+    if (index * this.blockSize >= this.length || this.blocks.has(index)) {
+      return;
+    }
+    // a failed read-ahead surfaces again if the block is actually requested
+    this.getBlock(index).catch(() => {});
+  }
+  private fetchBlock(index: number) {
+    const start = index * this.blockSize;
+    const end = Math.min(start + this.blockSize, this.length) - 1;
+    const block = Promise.resolve(this.media.createReadStream({ start, end }))
+      .then(streamToBuffer);
+    block.catch((error) => {
+      this.metrics?.addError(error);
+      if (this.blocks.get(index) === block) {
+        this.blocks.delete(index);
+      }
+    });
+    return block;
+  }
+  private evict() {
+    for (const index of this.blocks.keys()) {
+      if (this.blocks.size <= this.capacity) {
+        break;
+      }
+      this.blocks.delete(index);
+    }
+  }
+}
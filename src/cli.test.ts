@@ -0,0 +1,106 @@
+import { expect, test } from "vitest";
+import { PassThrough } from "stream";
+import path from "path";
+import fs from "fs";
+import { rm } from "fs/promises";
+import os from "os";
+
+import { run, USAGE } from "./cli.js";
+import { streamToBuffer } from "./stream-utils.js";
+
+const fixturePath = path.resolve(__dirname, "./__fixtures__");
+const firstVolume = path.join(fixturePath, "multi-splitted/multi-splitted.rar");
+
+const runCli = async (...args: string[]) => {
+  const stdout = new PassThrough();
+  const stderr = new PassThrough();
+  const code = await run(args, { stdout, stderr });
+  stdout.end();
+  stderr.end();
+  return {
+    code,
+    stdout: await streamToBuffer(stdout),
+    stderr: (await streamToBuffer(stderr)).toString(),
+  };
+};
+
+test("cli list prints every entry with its size", async () => {
+  const { code, stdout } = await runCli("list", firstVolume);
+  const lines = stdout.toString().trim().split("\n");
+  expect(code).toBe(0);
+  expect(lines.map((l) => l.split(/\s+/).pop())).toEqual([
+    "splitted1.txt",
+    "splitted2.txt",
+    "splitted3.txt",
+    "splitted4.txt",
+  ]);
+  const size = fs.statSync(
+    path.join(fixturePath, "multi-splitted/splitted4.txt")
+  ).size;
+  expect(lines[3]!.trim().startsWith(`${size} `)).toBe(true);
+});
+
+test("cli cat writes the entry to stdout", async () => {
+  const { code, stdout } = await runCli("cat", "splitted4.txt", firstVolume);
+  expect(code).toBe(0);
+  expect(stdout).toEqual(
+    fs.readFileSync(path.join(fixturePath, "multi-splitted/splitted4.txt"))
+  );
+});
+
+test("cli cat fails for unknown entries", async () => {
+  const { code, stderr } = await runCli("cat", "missing.txt", firstVolume);
+  expect(code).toBe(1);
+  expect(stderr).toBe("No file named missing.txt in the archive\n");
+});
+
+test("cli extract writes the entries to the directory", async () => {
+  const destination = fs.mkdtempSync(path.join(os.tmpdir(), "rar-stream-"));
+  try {
+    const { code } = await runCli("extract", destination, firstVolume);
+    expect(code).toBe(0);
+    expect(fs.readdirSync(destination).sort()).toEqual([
+      "splitted1.txt",
+      "splitted2.txt",
+      "splitted3.txt",
+      "splitted4.txt",
+    ]);
+  } finally {
+    await rm(destination, { recursive: true, force: true });
+  }
+});
+
+test("cli verify checks every entry", async () => {
+  const { code, stdout } = await runCli(
+    "verify",
+    path.join(fixturePath, "single/single.rar")
+  );
+  expect(code).toBe(0);
+  expect(stdout.toString()).toBe("OK      single.txt\n");
+});
+
+test("cli bench reports the bytes read", async () => {
+  const { code, stdout } = await runCli(
+    "bench",
+    path.join(fixturePath, "single/single.rar")
+  );
+  expect(code).toBe(0);
+  expect(stdout.toString()).toContain("read 1890 bytes");
+});
+
+test("cli rejects volumes that do not exist", async () => {
+  await expect(
+    runCli("list", path.join(fixturePath, "missing.rar"))
+  ).rejects.toThrow("No rar volumes found");
+});
+
+test("cli prints usage for missing arguments", async () => {
+  expect(await runCli("cat", firstVolume)).toEqual({
+    code: 1,
+    stdout: Buffer.alloc(0),
+    stderr: USAGE,
+  });
+  expect((await runCli("frobnicate", firstVolume)).stderr).toContain(
+    "Unknown command frobnicate"
+  );
+});
@@ -0,0 +1,133 @@
+import { Writable } from "stream";
+import { pipeline } from "stream/promises";
+import { CachedFileMedia } from "./cached-file-media.js";
+import { HttpFileMedia } from "./http-file-media.js";
+import { InnerFile } from "./inner-file.js";
+import { LocalFileMedia } from "./local-file-media.js";
+import { RarFilesPackage } from "./rar-files-package.js";
+import { sum } from "./utils.js";
+
+export const USAGE = `Usage: rar-stream <command> [argument] <volume...>
+
+Volumes are local paths or http(s) urls. A single local volume is enough,
+the rest of the set is looked up next to it.
+
+Commands:
+  list                  List the entries of the archive
+  extract <directory>   Extract every entry below directory
+  cat <entry>           Write the content of an entry to stdout
+  verify                Check the CRC32 of every entry
+  bench                 Measure parse time and read throughput
+`;
+
+export interface CliIO {
+  stdout: Writable;
+  stderr: Writable;
+}
+
+const isUrl = (source: string) => /^https?:\/\//i.test(source);
+
+export async function openPackage(sources: string[]) {
+  if (sources.length === 1 && !isUrl(sources[0]!)) {
+    return RarFilesPackage.fromFirstVolume(sources[0]!);
+  }
+  const medias = await Promise.all(
+    sources.map(async (source) =>
+      isUrl(source)
+        ? new CachedFileMedia(await HttpFileMedia.create(source))
+        : new LocalFileMedia(source)
+    )
+  );
+  return new RarFilesPackage(medias);
+}
+
+const readAll = async (innerFile: InnerFile) => {
+  let bytes = 0;
+  if (innerFile.length) {
+    const stream = await innerFile.createReadStream({
+      start: 0,
+      end: innerFile.length - 1,
+    });
+    for await (const data of stream) {
+      bytes += data.length;
+    }
+  }
+  return bytes;
+};
+
+export async function run(
+  args: string[],
+  io: CliIO = { stdout: process.stdout, stderr: process.stderr }
+) {
+  const [command, ...rest] = args;
+  const takesArgument = command === "extract" || command === "cat";
+  const argument = takesArgument ? rest.shift() : undefined;
+  if (!command || (takesArgument && !argument) || !rest.length) {
+    io.stderr.write(USAGE);
+    return 1;
+  }
+  const rarPackage = await openPackage(rest);
+
+  switch (command) {
+    case "list": {
+      for (const innerFile of await rarPackage.parse()) {
+        const modifiedAt = innerFile.modifiedAt?.toISOString() ?? "";
+        const name = innerFile.isDirectory ? `${innerFile.name}/` : innerFile.name;
+        io.stdout.write(
+          `${String(innerFile.length).padStart(12)}  ${modifiedAt}  ${name}\n`
+        );
+      }
+      return 0;
+    }
+    case "extract": {
+      const innerFiles = await rarPackage.extractAll(argument!);
+      io.stdout.write(`Extracted ${innerFiles.length} entries to ${argument}\n`);
+      return 0;
+    }
+    case "cat": {
+      const innerFiles = await rarPackage.parse({
+        filter: (name) => name === argument,
+        maxFiles: 1,
+      });
+      const [innerFile] = innerFiles;
+      if (!innerFile || innerFile.isDirectory) {
+        io.stderr.write(`No file named ${argument} in the archive\n`);
+        return 1;
+      }
+      if (innerFile.length) {
+        await pipeline(
+          await innerFile.createReadStream({ start: 0, end: innerFile.length - 1 }),
+          io.stdout,
+          { end: false }
+        );
+      }
+      return 0;
+    }
+    case "verify": {
+      const results = await rarPackage.test();
+      for (const { name, ok, error } of results) {
+        io.stdout.write(
+          ok ? `OK      ${name}\n` : `FAILED  ${error!.message}\n`
+        );
+      }
+      return results.every((r) => r.ok) ? 0 : 1;
+    }
+    case "bench": {
+      const parseStart = performance.now();
+      const innerFiles = await rarPackage.parse();
+      const parseTime = performance.now() - parseStart;
+      const readStart = performance.now();
+      const bytes = sum([0, ...(await Promise.all(innerFiles.map(readAll)))]);
+      const readTime = performance.now() - readStart;
+      const throughput = bytes / 1024 / 1024 / (readTime / 1000 || 1e-3);
+      io.stdout.write(
+        `parsed ${innerFiles.length} entries in ${parseTime.toFixed(1)} ms\n` +
+          `read ${bytes} bytes in ${readTime.toFixed(1)} ms (${throughput.toFixed(1)} MiB/s)\n`
+      );
+      return 0;
+    }
+    default:
+      io.stderr.write(`Unknown command ${command}\n\n${USAGE}`);
+      return 1;
+  }
+}
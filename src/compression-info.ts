@@ -0,0 +1,36 @@
+import { HostOS, hostName } from "./file-attributes.js";
+import { IFileHeader } from "./parsing/file-header-parser.js";
+
+const METHODS = ["store", "fastest", "fast", "normal", "good", "best"] as const;
+
+export type CompressionMethod = (typeof METHODS)[number] | "unknown";
+
+export interface ICompressionInfo {
+  method: CompressionMethod;
+  // bytes of history the decoder needs, not known for directories
+  dictionarySize?: number;
+  isSolid: boolean;
+  isEncrypted: boolean;
+  host: HostOS;
+  // the RAR version needed to extract the entry, e.g. 29 for 2.9
+  unpackVersion: number;
+}
+
+const DICTIONARY_FLAGS = 0xe0;
+const SOLID_FLAG = 0x10;
+
+// Describes how an entry was packed, from the method byte 0x30 to 0x35 and
+// the dictionary size in bits 5 to 7 of the flags.
+export function describeCompression(fileHead: IFileHeader): ICompressionInfo {
+  const dictionaryBits = (fileHead.flags & DICTIONARY_FLAGS) >> 5;
+  return {
+    method: METHODS[fileHead.method - 0x30] ?? "unknown",
+    ...(!fileHead.isDirectory && {
+      dictionarySize: 0x10000 << dictionaryBits,
+    }),
+    isSolid: !!(fileHead.flags & SOLID_FLAG),
+    isEncrypted: fileHead.isEncrypted,
+    host: hostName(fileHead.host),
+    unpackVersion: fileHead.version,
+  };
+}
@@ -0,0 +1,61 @@
+import { expect, test } from "vitest";
+import { sniffContentType } from "./content-type.js";
+
+const head = (...parts: (string | number[])[]) =>
+  Buffer.concat(
+    parts.map((part) =>
+      typeof part === "string" ? Buffer.from(part, "latin1") : Buffer.from(part)
+    )
+  );
+
+test("sniffContentType tells video containers apart", () => {
+  expect(
+    sniffContentType(head([0x1a, 0x45, 0xdf, 0xa3], "\x42\x82\x84webm"))
+  ).toBe("video/webm");
+  expect(
+    sniffContentType(head([0x1a, 0x45, 0xdf, 0xa3], "\x42\x82\x88matroska"))
+  ).toBe("video/x-matroska");
+  expect(sniffContentType(head([0, 0, 0, 0x20], "ftypisom"))).toBe("video/mp4");
+  expect(sniffContentType(head([0, 0, 0, 0x14], "ftypqt  "))).toBe(
+    "video/quicktime"
+  );
+  expect(sniffContentType(head("RIFF", [0, 0, 0, 0], "AVI LIST"))).toBe(
+    "video/x-msvideo"
+  );
+  const transportStream = Buffer.alloc(376);
+  transportStream[0] = transportStream[188] = 0x47;
+  expect(sniffContentType(transportStream)).toBe("video/mp2t");
+});
+
+test("sniffContentType detects audio", () => {
+  expect(sniffContentType(head("ID3", [4, 0]))).toBe("audio/mpeg");
+  expect(sniffContentType(head([0xff, 0xfb, 0x90]))).toBe("audio/mpeg");
+  expect(sniffContentType(head([0xff, 0xf1, 0x50]))).toBe("audio/aac");
+  expect(sniffContentType(head("fLaC"))).toBe("audio/flac");
+  expect(sniffContentType(head("OggS", [0, 2], "\x01vorbis"))).toBe(
+    "audio/ogg"
+  );
+  expect(sniffContentType(head("OggS", [0, 2], "OpusHead"))).toBe(
+    "audio/opus"
+  );
+  expect(sniffContentType(head([0, 0, 0, 0x20], "ftypM4A "))).toBe(
+    "audio/mp4"
+  );
+});
+
+test("sniffContentType detects images", () => {
+  expect(sniffContentType(head([0x89], "PNG\r\n\x1a\n"))).toBe("image/png");
+  expect(sniffContentType(head([0xff, 0xd8, 0xff, 0xe0]))).toBe("image/jpeg");
+  expect(sniffContentType(head("GIF89a"))).toBe("image/gif");
+  expect(sniffContentType(head("RIFF", [0, 0, 0, 0], "WEBPVP8 "))).toBe(
+    "image/webp"
+  );
+  expect(sniffContentType(head([0, 0, 0, 0x1c], "ftypavif"))).toBe(
+    "image/avif"
+  );
+});
+
+test("sniffContentType leaves other data undetected", () => {
+  expect(sniffContentType(Buffer.from("just some text"))).toBe(undefined);
+  expect(sniffContentType(Buffer.alloc(0))).toBe(undefined);
+});
@@ -0,0 +1,98 @@
+// Enough for every signature below, including the doctype of a matroska header.
+export const SNIFF_LENGTH = 4096;
+
+const startsWith = (head: Buffer, signature: string, offset = 0) =>
+  head.subarray(offset, offset + signature.length).toString("latin1") ===
+  signature;
+
+// ISO base media files name their major brand right after "ftyp".
+const isoBrandType = (brand: string) => {
+  switch (brand) {
+    case "qt  ":
+      return "video/quicktime";
+    case "M4A ":
+    case "M4B ":
+      return "audio/mp4";
+    case "avif":
+    case "avis":
+      return "image/avif";
+    case "heic":
+    case "heix":
+    case "mif1":
+    case "msf1":
+      return "image/heic";
+    default:
+      return brand.startsWith("3g") ? "video/3gpp" : "video/mp4";
+  }
+};
+
+// The first page of an ogg stream holds the header of its codec.
+const oggType = (head: Buffer) => {
+  if (head.includes("theora")) {
+    return "video/ogg";
+  }
+  return head.includes("OpusHead") ? "audio/opus" : "audio/ogg";
+};
+
+const RIFF_TYPES: Record<string, string> = {
+  "AVI ": "video/x-msvideo",
+  WAVE: "audio/wav",
+  WEBP: "image/webp",
+};
+
+const SIGNATURES: [string, string][] = [
+  ["\x89PNG\r\n\x1a\n", "image/png"],
+  ["\xff\xd8\xff", "image/jpeg"],
+  ["GIF87a", "image/gif"],
+  ["GIF89a", "image/gif"],
+  ["II*\0", "image/tiff"],
+  ["MM\0*", "image/tiff"],
+  ["BM", "image/bmp"],
+  ["FLV\x01", "video/x-flv"],
+  ["\x30\x26\xb2\x75\x8e\x66\xcf\x11", "video/x-ms-asf"],
+  ["\0\0\x01\xba", "video/mpeg"],
+  ["\0\0\x01\xb3", "video/mpeg"],
+  ["fLaC", "audio/flac"],
+  ["ID3", "audio/mpeg"],
+];
+
+const isMpegTransportStream = (head: Buffer) =>
+  head.length >= 189 && head[0] === 0x47 && head[188] === 0x47;
+
+// MPEG audio frames start with 11 set bits. Layer 00 is reserved, which is
+// where ADTS AAC frames differ.
+const mpegAudioType = (head: Buffer) => {
+  if (head[0] !== 0xff || ((head[1] ?? 0) & 0xe0) !== 0xe0) {
+    return undefined;
+  }
+  return ((head[1] ?? 0) & 0x06) === 0 ? "audio/aac" : "audio/mpeg";
+};
+
+// Detects common video, audio and image formats from the first bytes of a
+// file, see SNIFF_LENGTH.
+export function sniffContentType(head: Buffer): string | undefined {
+  if (startsWith(head, "\x1a\x45\xdf\xa3")) {
+    return head.includes("webm") ? "video/webm" : "video/x-matroska";
+  }
+  if (startsWith(head, "ftyp", 4)) {
+    return isoBrandType(head.subarray(8, 12).toString("latin1"));
+  }
+  if (startsWith(head, "RIFF")) {
+    return RIFF_TYPES[head.subarray(8, 12).toString("latin1")];
+  }
+  if (startsWith(head, "OggS")) {
+    return oggType(head);
+  }
+  if (startsWith(head, "FORM") && startsWith(head, "AIF", 8)) {
+    return "audio/aiff";
+  }
+  for (const [signature, type] of SIGNATURES) {
+    if (startsWith(head, signature)) {
+      return type;
+    }
+  }
+  if (isMpegTransportStream(head)) {
+    return "video/mp2t";
+  }
+  return mpegAudioType(head);
+}
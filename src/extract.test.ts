@@ -0,0 +1,255 @@
+import { expect, test } from "vitest";
+import path from "path";
+import fs from "fs";
+import os from "os";
+
+import { IExtractOpts, IExtractProgress } from "./interfaces.js";
+import { LocalFileMedia } from "./local-file-media.js";
+import { MemoryFileMedia } from "./memory-file-media.js";
+import { mockRarArchive } from "./parsing/__mocks__/mock-rar-archive.js";
+import { RarFilesPackage } from "./rar-files-package.js";
+
+const fixturePath = path.resolve(__dirname, "./__fixtures__");
+const DIRECTORY = 0xe0;
+// 2016-10-26 19:22:38 local time
+const TIMESTAMP = 0x495a9ad3;
+
+const tempDir = () => fs.mkdtempSync(path.join(os.tmpdir(), "rar-stream-"));
+
+test("extractAll writes every inner file across volumes", async () => {
+  const destination = tempDir();
+  const rarPackage = RarFilesPackage.fromFirstVolume(
+    path.join(fixturePath, "multi-splitted/multi-splitted.rar")
+  );
+  const progress: IExtractProgress[] = [];
+  await rarPackage.extractAll(destination, {
+    onProgress: (p) => progress.push(p),
+  });
+
+  for (const name of ["splitted1", "splitted2", "splitted3", "splitted4"]) {
+    expect(fs.readFileSync(path.join(destination, `${name}.txt`))).toEqual(
+      fs.readFileSync(path.join(fixturePath, `multi-splitted/${name}.txt`))
+    );
+  }
+  const last = progress[progress.length - 1]!;
+  expect(last.name).toBe("splitted4.txt");
+  expect(last.fileBytes).toBe(last.fileLength);
+  expect(last.bytes).toBe(last.totalBytes);
+});
+
+test("extractAll recreates directories and modification times", async () => {
+  const destination = tempDir();
+  const archive = mockRarArchive([
+    { name: "docs", flags: DIRECTORY, attributes: 0x41ed, timestamp: TIMESTAMP },
+    { name: "docs\\sub\\a.txt", data: Buffer.from("a"), timestamp: TIMESTAMP },
+    { name: "empty.txt" },
+  ]);
+  await new RarFilesPackage([
+    new MemoryFileMedia(archive, "tree.rar"),
+  ]).extractAll(destination);
+
+  const file = path.join(destination, "docs", "sub", "a.txt");
+  expect(fs.readFileSync(file, "utf-8")).toBe("a");
+  expect(fs.readFileSync(path.join(destination, "empty.txt")).length).toBe(0);
+
+  const expected = new Date(2016, 9, 26, 19, 22, 38).getTime();
+  expect(fs.statSync(file).mtime.getTime()).toBe(expected);
+  expect(fs.statSync(path.join(destination, "docs")).mtime.getTime()).toBe(
+    expected
+  );
+});
+
+test("extractAll refuses entries that escape the destination", async () => {
+  const destination = tempDir();
+  const archive = mockRarArchive([
+    { name: "../evil.txt", data: Buffer.from("evil") },
+  ]);
+  await expect(
+    new RarFilesPackage([new MemoryFileMedia(archive, "evil.rar")]).extractAll(
+      destination
+    )
+  ).rejects.toThrow("outside of");
+  expect(fs.existsSync(path.join(destination, "..", "evil.txt"))).toBe(false);
+});
+
+test("extractAll honours the filter option", async () => {
+  const destination = tempDir();
+  const rarPackage = new RarFilesPackage([
+    new LocalFileMedia(
+      path.join(fixturePath, "single-splitted/single-splitted.rar")
+    ),
+  ]);
+  await rarPackage.extractAll(destination, {
+    filter: (name) => name === "splitted2.txt",
+  });
+  expect(fs.readdirSync(destination)).toEqual(["splitted2.txt"]);
+});
+
+test("extractAll can make names safe for windows", async () => {
+  const destination = tempDir();
+  const archive = mockRarArchive([
+    { name: "C:\\docs\\what?.txt", data: Buffer.from("a") },
+  ]);
+  await new RarFilesPackage([
+    new MemoryFileMedia(archive, "windows.rar"),
+  ]).extractAll(destination, { windowsSafeNames: true });
+
+  expect(
+    fs.readFileSync(path.join(destination, "docs", "what_.txt"), "utf-8")
+  ).toBe("a");
+});
+
+const UNIX_HOST = 3;
+const SYMLINK = 0xa1ff;
+
+const archiveWithLink = (target: string) =>
+  mockRarArchive([
+    { name: "docs/a.txt", data: Buffer.from("a") },
+    {
+      name: "docs/link",
+      data: Buffer.from(target),
+      host: UNIX_HOST,
+      attributes: SYMLINK,
+    },
+  ]);
+
+const linkEntry = (name: string, target: string) => ({
+  name,
+  data: Buffer.from(target),
+  host: UNIX_HOST,
+  attributes: SYMLINK,
+});
+
+const extractWithLinks = async (
+  target: string,
+  symlinks?: "skip" | "link" | "dereference"
+) => {
+  const destination = tempDir();
+  await new RarFilesPackage([
+    new MemoryFileMedia(archiveWithLink(target), "links.rar"),
+  ]).extractAll(destination, symlinks ? { symlinks } : {});
+  return path.join(destination, "docs", "link");
+};
+
+test("extractAll skips symlinks by default", async () => {
+  const link = await extractWithLinks("a.txt");
+  expect(fs.existsSync(link)).toBe(false);
+});
+
+test("extractAll can recreate symlinks", async () => {
+  const link = await extractWithLinks("a.txt", "link");
+  expect(fs.readlinkSync(link)).toBe("a.txt");
+  expect(fs.readFileSync(link, "utf-8")).toBe("a");
+});
+
+test("extractAll can dereference symlinks", async () => {
+  const link = await extractWithLinks("a.txt", "dereference");
+  expect(fs.lstatSync(link).isSymbolicLink()).toBe(false);
+  expect(fs.readFileSync(link, "utf-8")).toBe("a");
+});
+
+test("extractAll refuses symlinks that escape the destination", async () => {
+  await expect(extractWithLinks("../../../etc/passwd", "link")).rejects.toThrow(
+    "Refusing to link docs/link to ../../../etc/passwd outside of"
+  );
+  await expect(extractWithLinks("/etc/passwd", "dereference")).rejects.toThrow(
+    "Refusing to link docs/link to /etc/passwd outside of"
+  );
+});
+
+test("extractAll refuses symlinks created through earlier symlinks", async () => {
+  const destination = tempDir();
+  await expect(
+    new RarFilesPackage([
+      new MemoryFileMedia(
+        mockRarArchive([linkEntry("a/b", ".."), linkEntry("a/b/c/f", "../../y")]),
+        "chain.rar"
+      ),
+    ]).extractAll(destination, { symlinks: "link" })
+  ).rejects.toThrow("Refusing to link a/b/c/f through the link");
+  expect(fs.existsSync(path.join(destination, "c"))).toBe(false);
+});
+
+test("extractAll refuses link targets that pass through earlier symlinks", async () => {
+  const destination = tempDir();
+  // d/x is the destination, so d/x/.. is its parent on disk, while the
+  // text of the path resolves to d
+  await expect(
+    new RarFilesPackage([
+      new MemoryFileMedia(
+        mockRarArchive([linkEntry("d/x", ".."), linkEntry("y", "d/x/..")]),
+        "chain.rar"
+      ),
+    ]).extractAll(destination, { symlinks: "link" })
+  ).rejects.toThrow("Refusing to link y through the link");
+  expect(fs.existsSync(path.join(destination, "y"))).toBe(false);
+});
+
+test("extractAll refuses symlinks named after the destination", async () => {
+  const destination = tempDir();
+  await expect(
+    new RarFilesPackage([
+      new MemoryFileMedia(mockRarArchive([linkEntry(".", "a")]), "root.rar"),
+    ]).extractAll(destination, { symlinks: "link" })
+  ).rejects.toThrow("with the link .");
+  expect(fs.lstatSync(destination).isDirectory()).toBe(true);
+});
+
+const SERVICE_HEADER = 0x7a;
+
+test("extractAll can write alternate data streams next to their file", async () => {
+  const destination = tempDir();
+  const archive = mockRarArchive([
+    { name: "a.txt", data: Buffer.from("a") },
+    {
+      name: "STM",
+      type: SERVICE_HEADER,
+      subData: Buffer.from(":Zone.Identifier:$DATA"),
+      data: Buffer.from("[ZoneTransfer]"),
+    },
+    { name: "b.txt", data: Buffer.from("b") },
+  ]);
+  const extract = (opts: IExtractOpts = {}) =>
+    new RarFilesPackage([new MemoryFileMedia(archive, "ads.rar")]).extractAll(
+      destination,
+      opts
+    );
+
+  await extract();
+  expect(fs.readdirSync(destination).sort()).toEqual(["a.txt", "b.txt"]);
+
+  await extract({ includeStreams: true });
+  expect(fs.readdirSync(destination).sort()).toEqual([
+    "a.txt",
+    "a.txt:Zone.Identifier",
+    "b.txt",
+  ]);
+  expect(
+    fs.readFileSync(path.join(destination, "a.txt:Zone.Identifier"), "utf-8")
+  ).toBe("[ZoneTransfer]");
+});
+
+test("extractAll applies unix modes and windows read-only flags", async () => {
+  const WINDOWS_HOST = 2;
+  const archive = mockRarArchive([
+    { name: "script.sh", host: UNIX_HOST, attributes: 0x8000 | 0o4750 },
+    { name: "locked.txt", host: WINDOWS_HOST, attributes: 0x21 },
+    { name: "open.txt", host: WINDOWS_HOST, attributes: 0x20 },
+  ]);
+  const extract = async (opts: IExtractOpts = {}) => {
+    const destination = tempDir();
+    await new RarFilesPackage([
+      new MemoryFileMedia(archive, "modes.rar"),
+    ]).extractAll(destination, opts);
+    return (name: string) =>
+      fs.statSync(path.join(destination, name)).mode & 0o7777;
+  };
+
+  const mode = await extract();
+  expect(mode("script.sh")).toBe(0o750);
+  expect(mode("locked.txt") & 0o222).toBe(0);
+  expect(mode("open.txt") & 0o200).toBe(0o200);
+
+  const defaultMode = await extract({ preservePermissions: false });
+  expect(defaultMode("script.sh") & 0o100).toBe(0);
+});
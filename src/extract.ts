@@ -0,0 +1,200 @@
+import { createWriteStream } from "fs";
+import {
+  chmod,
+  cp,
+  mkdir,
+  realpath,
+  stat,
+  symlink,
+  utimes,
+} from "fs/promises";
+import { dirname, isAbsolute, join, resolve, sep } from "path";
+import { Transform } from "stream";
+import { pipeline } from "stream/promises";
+import { InnerFile } from "./inner-file.js";
+import { IExtractOpts, IServiceEntry } from "./interfaces.js";
+import { sum } from "./utils.js";
+
+const isInside = (root: string, target: string) =>
+  target === root || target.startsWith(root + sep);
+
+const resolveTarget = (root: string, name: string) => {
+  const target = resolve(root, name);
+  if (!isInside(root, target)) {
+    throw new Error(`Refusing to extract ${name} outside of ${root}`);
+  }
+  return target;
+};
+
+// Links are created in archive order, so the text of a path can differ from
+// where it ends up on disk. Entries below an earlier link and targets that
+// pass through one are refused, and the target is checked against the real
+// parent directory.
+async function extractLink(
+  root: string,
+  target: string,
+  innerFile: InnerFile,
+  policy: "link" | "dereference",
+  created: Set<string>
+) {
+  const linkTarget = (await innerFile.linkTarget())!;
+  const refuse = () =>
+    new Error(
+      `Refusing to link ${innerFile.name} to ${linkTarget} outside of ${root}`
+    );
+  if (isAbsolute(linkTarget)) {
+    throw refuse();
+  }
+  if (target === root) {
+    throw new Error(`Refusing to replace ${root} with the link ${innerFile.name}`);
+  }
+  const through = (dir: string) =>
+    new Error(`Refusing to link ${innerFile.name} through the link ${dir}`);
+  for (let dir = dirname(target); dir !== root; dir = dirname(dir)) {
+    if (created.has(dir)) {
+      throw through(dir);
+    }
+  }
+  // follow the target one component at a time, as resolve would drop
+  // "link/.." without looking at what the link points to
+  let followed = dirname(target);
+  for (const part of linkTarget.split(/[\\/]/)) {
+    if (part === "" || part === ".") {
+      continue;
+    }
+    followed = part === ".." ? dirname(followed) : join(followed, part);
+    if (created.has(followed)) {
+      throw through(followed);
+    }
+  }
+  await mkdir(dirname(target), { recursive: true });
+  const realRoot = await realpath(root);
+  const parent = await realpath(dirname(target));
+  const resolved = resolve(parent, linkTarget);
+  if (!isInside(realRoot, parent) || !isInside(realRoot, resolved)) {
+    throw refuse();
+  }
+  if (policy === "link") {
+    await symlink(linkTarget, target);
+    created.add(target);
+  } else {
+    await cp(resolved, target, { recursive: true });
+  }
+}
+
+export async function extractFiles(
+  innerFiles: InnerFile[],
+  destination: string,
+  opts: IExtractOpts = {},
+  streams: IServiceEntry[] = []
+) {
+  const root = resolve(destination);
+  const totalBytes = sum([
+    0,
+    ...innerFiles.filter((f) => !f.isSymlink).map((f) => f.length),
+  ]);
+  const directories: [string, InnerFile][] = [];
+  const links: [string, InnerFile][] = [];
+  let bytes = 0;
+
+  for (const innerFile of innerFiles) {
+    const target = resolveTarget(
+      root,
+      innerFile.safePath(opts.windowsSafeNames)
+    );
+    if (innerFile.isDirectory) {
+      await mkdir(target, { recursive: true });
+      directories.push([target, innerFile]);
+      continue;
+    }
+    if (innerFile.isSymlink) {
+      links.push([target, innerFile]);
+      continue;
+    }
+    await mkdir(dirname(target), { recursive: true });
+
+    let fileBytes = 0;
+    const progress = new Transform({
+      transform(data: Buffer, _encoding, callback) {
+        fileBytes += data.length;
+        bytes += data.length;
+        opts.onProgress?.({
+          name: innerFile.name,
+          fileBytes,
+          fileLength: innerFile.length,
+          bytes,
+          totalBytes,
+        });
+        callback(null, data);
+      },
+    });
+    await pipeline(
+      await innerFile.createReadStream({ start: 0, end: innerFile.length - 1 }),
+      progress,
+      createWriteStream(target)
+    );
+    for (const stream of streams.filter((s) => s.owner === innerFile.name)) {
+      await extractStream(target, stream);
+    }
+    if (opts.preservePermissions !== false) {
+      await setPermissions(target, innerFile);
+    }
+    await setModifiedAt(target, innerFile);
+  }
+
+  // links may point at entries that come later in the archive
+  const policy = opts.symlinks ?? "skip";
+  if (policy !== "skip") {
+    const created = new Set<string>();
+    for (const [target, innerFile] of links) {
+      await extractLink(root, target, innerFile, policy, created);
+    }
+  }
+
+  // writing files touches their parent directories, so these go last
+  for (const [target, innerFile] of directories.reverse()) {
+    if (opts.preservePermissions !== false) {
+      await setPermissions(target, innerFile);
+    }
+    await setModifiedAt(target, innerFile);
+  }
+  return innerFiles;
+}
+
+// NTFS alternate data streams are written as "file:stream"
+async function extractStream(
+  target: string,
+  { owner, streamName, file }: IServiceEntry
+) {
+  if (!file || streamName === undefined) {
+    return;
+  }
+  if (/[\\/]/.test(streamName)) {
+    throw new Error(`Refusing to extract stream ${streamName} of ${owner}`);
+  }
+  await pipeline(
+    await file.createReadStream({ start: 0, end: file.length - 1 }),
+    createWriteStream(`${target}:${streamName}`)
+  );
+}
+
+// Unix modes are applied without setuid, setgid and sticky bits. Other
+// hosts only have a read-only flag, which clears the write bits.
+async function setPermissions(target: string, innerFile: InnerFile) {
+  const attributes = innerFile.attributes;
+  if (!attributes) {
+    return;
+  }
+  if (attributes.mode !== undefined) {
+    await chmod(target, attributes.mode & 0o777);
+  } else if (attributes.isReadOnly) {
+    await chmod(target, (await stat(target)).mode & 0o777 & ~0o222);
+  }
+}
+
+async function setModifiedAt(target: string, innerFile: InnerFile) {
+  const modifiedAt = innerFile.modifiedAt;
+  if (modifiedAt) {
+    await utimes(target, innerFile.accessedAt ?? modifiedAt, modifiedAt);
+  }
+}
@@ -0,0 +1,36 @@
+import { expect, test } from "vitest";
+import { decodeAttributes } from "./file-attributes.js";
+
+test("decodeAttributes reads unix modes", () => {
+  expect(decodeAttributes(3, 0x81a4)).toEqual({
+    host: "unix",
+    mode: 0o644,
+    isReadOnly: false,
+    isHidden: false,
+    isSystem: false,
+    isDirectory: false,
+    isSymlink: false,
+  });
+  expect(decodeAttributes(3, 0x4000 | 0o555)).toMatchObject({
+    mode: 0o555,
+    isReadOnly: true,
+    isDirectory: true,
+  });
+  expect(decodeAttributes(3, 0xa1ff).isSymlink).toBe(true);
+});
+
+test("decodeAttributes reads windows attribute flags", () => {
+  expect(decodeAttributes(2, 0x01 | 0x02 | 0x20)).toEqual({
+    host: "windows",
+    isReadOnly: true,
+    isHidden: true,
+    isSystem: false,
+    isDirectory: false,
+    isSymlink: false,
+  });
+  expect(decodeAttributes(0, 0x10)).toMatchObject({
+    host: "msdos",
+    isDirectory: true,
+  });
+  expect(decodeAttributes(9, 0).host).toBe("unknown");
+});
@@ -0,0 +1,55 @@
+const HOSTS = ["msdos", "os2", "windows", "unix", "macos", "beos"] as const;
+
+export type HostOS = (typeof HOSTS)[number] | "unknown";
+
+export const hostName = (host: number): HostOS => HOSTS[host] ?? "unknown";
+
+export interface IFileAttributes {
+  host: HostOS;
+  // unix permission bits, only known for unix hosts
+  mode?: number;
+  isReadOnly: boolean;
+  isHidden: boolean;
+  isSystem: boolean;
+  isDirectory: boolean;
+  isSymlink: boolean;
+}
+
+const S_IFMT = 0xf000;
+const S_IFDIR = 0x4000;
+const S_IFLNK = 0xa000;
+
+const FILE_ATTRIBUTE_READONLY = 0x01;
+const FILE_ATTRIBUTE_HIDDEN = 0x02;
+const FILE_ATTRIBUTE_SYSTEM = 0x04;
+const FILE_ATTRIBUTE_DIRECTORY = 0x10;
+const FILE_ATTRIBUTE_REPARSE_POINT = 0x400;
+
+// The attributes field holds a unix mode or DOS/Windows attribute flags
+// depending on the host the archive was made on.
+export function decodeAttributes(
+  host: number,
+  attributes: number
+): IFileAttributes {
+  const hostOS = hostName(host);
+  if (hostOS === "unix" || hostOS === "beos") {
+    const mode = attributes & 0o7777;
+    return {
+      host: hostOS,
+      mode,
+      isReadOnly: !(mode & 0o200),
+      isHidden: false,
+      isSystem: false,
+      isDirectory: (attributes & S_IFMT) === S_IFDIR,
+      isSymlink: (attributes & S_IFMT) === S_IFLNK,
+    };
+  }
+  return {
+    host: hostOS,
+    isReadOnly: !!(attributes & FILE_ATTRIBUTE_READONLY),
+    isHidden: !!(attributes & FILE_ATTRIBUTE_HIDDEN),
+    isSystem: !!(attributes & FILE_ATTRIBUTE_SYSTEM),
+    isDirectory: !!(attributes & FILE_ATTRIBUTE_DIRECTORY),
+    isSymlink: !!(attributes & FILE_ATTRIBUTE_REPARSE_POINT),
+  };
+}
@@ -0,0 +1,65 @@
+import { expect, test } from "vitest";
+
+import { findNextHeader, findSignature, validateHeader } from "./header-scan.js";
+import { MemoryFileMedia } from "./memory-file-media.js";
+import { mockRarArchive } from "./parsing/__mocks__/mock-rar-archive.js";
+
+const FIRST_HEADER = 7 + 13;
+
+const newArchive = () =>
+  mockRarArchive([
+    { name: "a.txt", data: Buffer.from("first") },
+    { name: "b.txt", data: Buffer.from("second") },
+  ]);
+
+test("validateHeader accepts sound headers", async () => {
+  const media = new MemoryFileMedia(newArchive(), "a.rar");
+  expect(await validateHeader(media, FIRST_HEADER)).toBe(undefined);
+});
+
+test("validateHeader detects header CRC mismatches", async () => {
+  const archive = newArchive();
+  archive[FIRST_HEADER + 32] = "x".charCodeAt(0);
+  const media = new MemoryFileMedia(archive, "a.rar");
+  expect(await validateHeader(media, FIRST_HEADER)).toMatch(
+    /^header CRC mismatch/
+  );
+});
+
+test("validateHeader detects data running past the volume", async () => {
+  const archive = newArchive().subarray(0, FIRST_HEADER + 32 + 5 + 2);
+  const media = new MemoryFileMedia(archive, "a.rar");
+  expect(await validateHeader(media, FIRST_HEADER)).toBe(
+    "data size 5 runs past the end of the volume"
+  );
+});
+
+test("findNextHeader skips to the next sound header", async () => {
+  const media = new MemoryFileMedia(newArchive(), "a.rar");
+  const second = FIRST_HEADER + 32 + 5 + 5;
+  expect(await findNextHeader(media, FIRST_HEADER + 1)).toBe(second);
+  expect(await findNextHeader(media, second + 1)).toBe(media.length - 7);
+});
+
+const RAR5_SIGNATURE = Buffer.from("526172211a070100", "hex");
+
+test("findSignature finds a RAR 4 marker behind leading bytes", async () => {
+  const junk = Buffer.alloc(64 * 1024 - 3, "MZ");
+  const media = new MemoryFileMedia(
+    Buffer.concat([junk, newArchive()]),
+    "sfx.exe"
+  );
+  expect(await findSignature(media, 1024 * 1024)).toEqual({
+    offset: junk.length,
+    version: 4,
+  });
+  expect(await findSignature(media, 1000)).toBe(undefined);
+});
+
+test("findSignature tells RAR 5 markers apart", async () => {
+  const media = new MemoryFileMedia(
+    Buffer.concat([Buffer.from("junk"), RAR5_SIGNATURE, Buffer.alloc(20)]),
+    "a.rar"
+  );
+  expect(await findSignature(media, 100)).toEqual({ offset: 4, version: 5 });
+});
@@ -0,0 +1,131 @@
+import { IFileMedia } from "./interfaces.js";
+import { streamToBuffer } from "./stream-utils.js";
+import { crc32 } from "./utils.js";
+
+export const BASE_HEADER_SIZE = 7;
+export const MARKER_HEADER_TYPE = 0x72;
+export const ARCHIVE_HEADER_TYPE = 0x73;
+export const FILE_HEADER_TYPE = 0x74;
+export const SERVICE_HEADER_TYPE = 0x7a;
+export const TERMINATOR_HEADER_TYPE = 0x7b;
+const LONG_BLOCK_FLAG = 0x8000;
+const HIGH_SIZE_FLAG = 0x100;
+const SCAN_WINDOW_SIZE = 64 * 1024;
+const SIGNATURE_PREFIX = Buffer.from("Rar!\x1a\x07", "latin1");
+const RAR5_SIGNATURE_LENGTH = 8;
+
+// Reads past the end stop at the last byte, as header reads near the end of
+// a volume may ask for more than is left, and inner files used as volumes
+// reject those.
+export const readRange = async (
+  fileMedia: IFileMedia,
+  start: number,
+  end: number
+) =>
+  streamToBuffer(
+    await fileMedia.createReadStream({
+      start,
+      end: Math.min(end, fileMedia.length - 1),
+    })
+  );
+
+// Returns the number of data bytes following the block header in raw, e.g.
+// the packed file data, including the high 32 bits of large file headers.
+export const blockDataSize = (raw: Buffer) => {
+  const type = raw[2];
+  const flags = raw.readUInt16LE(3);
+  const hasFileLayout = type === FILE_HEADER_TYPE || type === SERVICE_HEADER_TYPE;
+  if (
+    !(hasFileLayout || flags & LONG_BLOCK_FLAG) ||
+    raw.length < BASE_HEADER_SIZE + 4
+  ) {
+    return 0;
+  }
+  let size = raw.readUInt32LE(7);
+  if (hasFileLayout && flags & HIGH_SIZE_FLAG && raw.length >= 36) {
+    size += raw.readUInt32LE(32) * 0x100000000;
+  }
+  return size;
+};
+
+// Returns why the block header at offset is broken, or undefined if it is sound.
+export async function validateHeader(fileMedia: IFileMedia, offset: number) {
+  if (offset + BASE_HEADER_SIZE > fileMedia.length) {
+    return "header is truncated";
+  }
+  const base = await readRange(fileMedia, offset, offset + BASE_HEADER_SIZE - 1);
+  const headSize = base.readUInt16LE(5);
+  if (headSize < BASE_HEADER_SIZE) {
+    return `header size ${headSize} is too small`;
+  }
+  if (offset + headSize > fileMedia.length) {
+    return `header size ${headSize} runs past the end of the volume`;
+  }
+  const header = await readRange(fileMedia, offset, offset + headSize - 1);
+  const expected = header.readUInt16LE(0);
+  const actual = crc32(header.subarray(2)) & 0xffff;
+  if (expected !== actual) {
+    return `header CRC mismatch, expected ${expected.toString(16)}, got ${actual.toString(16)}`;
+  }
+  const dataSize = blockDataSize(header);
+  if (offset + headSize + dataSize > fileMedia.length) {
+    return `data size ${dataSize} runs past the end of the volume`;
+  }
+  return undefined;
+}
+
+// Looks for the next sound file or terminator header at or after offset.
+export async function findNextHeader(fileMedia: IFileMedia, offset: number) {
+  for (
+    let start = offset;
+    start + BASE_HEADER_SIZE <= fileMedia.length;
+    start += SCAN_WINDOW_SIZE
+  ) {
+    // overlap by the type offset so headers across windows are not missed
+    const end = Math.min(start + SCAN_WINDOW_SIZE + 2, fileMedia.length) - 1;
+    const window = await readRange(fileMedia, start, end);
+    for (let i = 0; i < Math.min(window.length, SCAN_WINDOW_SIZE); i++) {
+      const type = window[i + 2];
+      if (
+        (type === FILE_HEADER_TYPE || type === TERMINATOR_HEADER_TYPE) &&
+        !(await validateHeader(fileMedia, start + i))
+      ) {
+        return start + i;
+      }
+    }
+  }
+  return undefined;
+}
+
+export interface ISignatureMatch {
+  offset: number;
+  version: 4 | 5;
+}
+
+// Finds the first RAR 1.5-4.x or RAR 5 marker starting within maxScanBytes.
+export async function findSignature(
+  fileMedia: IFileMedia,
+  maxScanBytes: number
+): Promise<ISignatureMatch | undefined> {
+  const scanEnd = Math.min(maxScanBytes, fileMedia.length);
+  for (let start = 0; start < scanEnd; start += SCAN_WINDOW_SIZE) {
+    const end =
+      Math.min(start + SCAN_WINDOW_SIZE + RAR5_SIGNATURE_LENGTH, fileMedia.length) - 1;
+    const window = await readRange(fileMedia, start, end);
+    let index = window.indexOf(SIGNATURE_PREFIX);
+    while (index !== -1 && index < SCAN_WINDOW_SIZE && start + index < scanEnd) {
+      const suffix = window.subarray(
+        index + SIGNATURE_PREFIX.length,
+        index + RAR5_SIGNATURE_LENGTH
+      );
+      if (suffix[0] === 0x00) {
+        return { offset: start + index, version: 4 };
+      }
+      if (suffix[0] === 0x01 && suffix[1] === 0x00) {
+        return { offset: start + index, version: 5 };
+      }
+      index = window.indexOf(SIGNATURE_PREFIX, index + 1);
+    }
+  }
+  return undefined;
+}
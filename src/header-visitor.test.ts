@@ -0,0 +1,74 @@
+import { expect, test } from "vitest";
+
+import { visitHeaders } from "./header-visitor.js";
+import { MemoryFileMedia } from "./memory-file-media.js";
+import { mockRarArchive } from "./parsing/__mocks__/mock-rar-archive.js";
+
+const archive = () =>
+  new MemoryFileMedia(
+    mockRarArchive([
+      { name: "a.txt", data: Buffer.from("first") },
+      { name: "CMT", data: Buffer.from("note"), type: 0x7a },
+      { name: "b.txt", data: Buffer.from("second") },
+    ]),
+    "a.rar"
+  );
+
+test("visitHeaders visits every block in order", async () => {
+  const blocks: [number, number, number][] = [];
+  await visitHeaders(
+    [archive()],
+    {
+      block: ({ type, offset, dataSize }) => {
+        blocks.push([type, offset, dataSize]);
+      },
+    },
+    1024
+  );
+  expect(blocks).toEqual([
+    [0x72, 0, 0],
+    [0x73, 7, 0],
+    [0x74, 20, 5],
+    [0x7a, 62, 4],
+    [0x74, 101, 6],
+    [0x7b, 144, 0],
+  ]);
+});
+
+test("visitHeaders passes typed headers with their raw bytes", async () => {
+  const seen: string[] = [];
+  await visitHeaders(
+    [archive(), archive()],
+    {
+      archive: (header, { volumeIndex }) => {
+        seen.push(`archive ${volumeIndex} ${header.isFirstVolume}`);
+      },
+      file: async (header, { raw }) => {
+        seen.push(`file ${header.name} ${raw.length}`);
+      },
+      service: (header) => {
+        seen.push(`service ${header.name}`);
+      },
+      end: (_, { volume }) => {
+        seen.push(`end ${volume.name}`);
+      },
+    },
+    1024
+  );
+  expect(seen.slice(0, 5)).toEqual([
+    "archive 0 false",
+    "file a.txt 37",
+    "service CMT",
+    "file b.txt 37",
+    "end a.rar",
+  ]);
+  expect(seen.length).toBe(10);
+});
+
+test("visitHeaders rejects broken headers", async () => {
+  const broken = mockRarArchive([{ name: "a.txt" }]);
+  broken.writeUInt16LE(3, 20 + 5);
+  await expect(
+    visitHeaders([new MemoryFileMedia(broken, "a.rar")], {}, 1024)
+  ).rejects.toThrow("Invalid header in a.rar at offset 20: header size 3");
+});
@@ -0,0 +1,133 @@
+import {
+  ARCHIVE_HEADER_TYPE,
+  BASE_HEADER_SIZE,
+  blockDataSize,
+  FILE_HEADER_TYPE,
+  findSignature,
+  MARKER_HEADER_TYPE,
+  readRange,
+  SERVICE_HEADER_TYPE,
+  TERMINATOR_HEADER_TYPE,
+} from "./header-scan.js";
+import { IFileMedia, IParser, IParsers } from "./interfaces.js";
+import { ArchiveHeaderParser } from "./parsing/archive-header-parser.js";
+import { FileHeaderParser, IFileHeader } from "./parsing/file-header-parser.js";
+import { MarkerHeaderParser } from "./parsing/marker-header-parser.js";
+import { TerminatorHeaderParser } from "./parsing/terminator-header-parser.js";
+import { RarError } from "./rar-error.js";
+
+export type IMarkerHeader = ReturnType<MarkerHeaderParser["parse"]>;
+export type IArchiveHeader = ReturnType<ArchiveHeaderParser["parse"]>;
+export type ITerminatorHeader = ReturnType<TerminatorHeaderParser["parse"]>;
+
+export interface IRawBlock {
+  volume: IFileMedia;
+  volumeIndex: number;
+  offset: number;
+  type: number;
+  flags: number;
+  headSize: number;
+  // bytes of data following the header, e.g. the packed file data
+  dataSize: number;
+  // the header bytes, starting with the header CRC
+  raw: Buffer;
+}
+
+// Every callback is optional and may return a promise, which is awaited
+// before the next header is read.
+export interface IHeaderVisitor {
+  // called for every block, including unknown ones, before the typed callback
+  block?(block: IRawBlock): unknown;
+  marker?(header: IMarkerHeader, block: IRawBlock): unknown;
+  archive?(header: IArchiveHeader, block: IRawBlock): unknown;
+  file?(header: IFileHeader, block: IRawBlock): unknown;
+  // service blocks such as comments and recovery records share the file layout
+  service?(header: IFileHeader, block: IRawBlock): unknown;
+  end?(header: ITerminatorHeader, block: IRawBlock): unknown;
+}
+
+// The parsers read a fixed number of bytes, which short headers lack.
+const padded = (raw: Buffer, size: number) =>
+  raw.length >= size ? raw : Buffer.concat([raw, Buffer.alloc(size - raw.length)]);
+
+const parseBlock = <T extends IParsers>(Parser: IParser<T>, raw: Buffer) =>
+  new Parser(padded(raw, Parser.HEADER_SIZE)).parse() as ReturnType<T["parse"]>;
+
+async function visitBlock(visitor: IHeaderVisitor, block: IRawBlock) {
+  await visitor.block?.(block);
+  switch (block.type) {
+    case MARKER_HEADER_TYPE:
+      await visitor.marker?.(parseBlock(MarkerHeaderParser, block.raw), block);
+      break;
+    case ARCHIVE_HEADER_TYPE:
+      await visitor.archive?.(parseBlock(ArchiveHeaderParser, block.raw), block);
+      break;
+    case FILE_HEADER_TYPE:
+      await visitor.file?.(parseBlock(FileHeaderParser, block.raw), block);
+      break;
+    case SERVICE_HEADER_TYPE:
+      await visitor.service?.(parseBlock(FileHeaderParser, block.raw), block);
+      break;
+    case TERMINATOR_HEADER_TYPE:
+      await visitor.end?.(parseBlock(TerminatorHeaderParser, block.raw), block);
+      break;
+  }
+}
+
+// Walks the blocks of each volume in order, from the signature up to the end
+// of archive block, without interpreting the file list.
+export async function visitHeaders(
+  volumes: IFileMedia[],
+  visitor: IHeaderVisitor,
+  maxScanBytes: number
+) {
+  for (const [volumeIndex, volume] of volumes.entries()) {
+    const context = { volume: volume.name, volumeIndex };
+    const signature = await findSignature(volume, maxScanBytes);
+    if (!signature) {
+      throw new RarError(
+        "NO_SIGNATURE",
+        `No RAR signature found in the first ${maxScanBytes} bytes of ${volume.name}`,
+        context
+      );
+    }
+    if (signature.version === 5) {
+      throw new RarError(
+        "UNSUPPORTED_VERSION",
+        `${volume.name} is a RAR 5 archive, which is not supported`,
+        { ...context, offset: signature.offset }
+      );
+    }
+    let offset = signature.offset;
+    while (offset + BASE_HEADER_SIZE <= volume.length) {
+      const base = await readRange(volume, offset, offset + BASE_HEADER_SIZE - 1);
+      const headSize = base.readUInt16LE(5);
+      if (headSize < BASE_HEADER_SIZE || offset + headSize > volume.length) {
+        throw new RarError(
+          "INVALID_HEADER",
+          `Invalid header in ${volume.name} at offset ${offset}: header size ${headSize}`,
+          { ...context, offset }
+        );
+      }
+      const raw =
+        headSize === BASE_HEADER_SIZE
+          ? base
+          : await readRange(volume, offset, offset + headSize - 1);
+      const block: IRawBlock = {
+        volume,
+        volumeIndex,
+        offset,
+        type: raw.readUInt8(2),
+        flags: raw.readUInt16LE(3),
+        headSize,
+        dataSize: blockDataSize(raw),
+        raw,
+      };
+      await visitBlock(visitor, block);
+      if (block.type === TERMINATOR_HEADER_TYPE) {
+        break;
+      }
+      offset += headSize + block.dataSize;
+    }
+  }
+}
@@ -0,0 +1,98 @@
+import { afterAll, beforeAll, expect, test } from "vitest";
+import { createServer, Server } from "http";
+import { AddressInfo } from "net";
+import path from "path";
+import fs from "fs";
+
+import { HttpFileMedia } from "./http-file-media.js";
+import { RarFilesPackage } from "./rar-files-package.js";
+import { streamToBuffer } from "./stream-utils.js";
+
+const fixturePath = path.resolve(__dirname, "./__fixtures__");
+const failures: Record<string, number> = {};
+let server: Server;
+let baseUrl: string;
+
+beforeAll(async () => {
+  server = createServer((req, res) => {
+    const url = new URL(req.url!, "http://localhost");
+    if (failures[url.pathname]) {
+      failures[url.pathname]!--;
+      res.writeHead(503).end();
+      return;
+    }
+    const file = path.join(fixturePath, url.pathname);
+    const { size } = fs.statSync(file);
+    const range = /bytes=(\d+)-(\d+)/.exec(req.headers.range ?? "");
+    if (!range) {
+      res.writeHead(200, { "Content-Length": size }).end();
+      return;
+    }
+    const start = +range[1]!;
+    const end = Math.min(+range[2]!, size - 1);
+    res.writeHead(206, {
+      "Content-Length": end - start + 1,
+      "Content-Range": `bytes ${start}-${end}/${size}`,
+    });
+    fs.createReadStream(file, { start, end }).pipe(res);
+  });
+  await new Promise<void>((resolve) => server.listen(0, resolve));
+  baseUrl = `http://localhost:${(server.address() as AddressInfo).port}`;
+});
+
+afterAll(() => {
+  server.close();
+});
+
+test("HttpFileMedia.create should read name and length from the url", async () => {
+  const media = await HttpFileMedia.create(`${baseUrl}/single/single.rar`);
+  expect(media.name).toBe("single.rar");
+  expect(media.length).toBe(1959);
+});
+
+test("HttpFileMedia#createReadStream should return an inclusive byte range", async () => {
+  const media = await HttpFileMedia.create(`${baseUrl}/single/single.txt`);
+  const buffer = await streamToBuffer(
+    await media.createReadStream({ start: 10, end: 19 })
+  );
+  const expected = fs
+    .readFileSync(path.join(fixturePath, "single/single.txt"))
+    .subarray(10, 20);
+  expect(buffer).toEqual(expected);
+});
+
+test("HttpFileMedia#createReadStream should retry failed requests", async () => {
+  const media = await HttpFileMedia.create(`${baseUrl}/single/single.txt`, {
+    retryDelay: 1,
+  });
+  failures["/single/single.txt"] = 2;
+  const buffer = await streamToBuffer(
+    await media.createReadStream({ start: 0, end: 3 })
+  );
+  expect(buffer.length).toBe(4);
+});
+
+test("HttpFileMedia#createReadStream should give up after all retries", async () => {
+  const media = await HttpFileMedia.create(`${baseUrl}/single/single.txt`, {
+    retries: 1,
+    retryDelay: 1,
+  });
+  failures["/single/single.txt"] = 2;
+  await expect(media.createReadStream({ start: 0, end: 3 })).rejects.toThrow(
+    "status 503"
+  );
+  delete failures["/single/single.txt"];
+});
+
+test("rar package can parse and read volumes served over http", async () => {
+  const medias = await Promise.all(
+    ["multi/multi.rar", "multi/multi.r00", "multi/multi.r01"].map((p) =>
+      HttpFileMedia.create(`${baseUrl}/${p}`, { concurrency: 1 })
+    )
+  );
+  const [file] = await new RarFilesPackage(medias).parse();
+  const content = await file!.readToEnd();
+  expect(content).toEqual(
+    fs.readFileSync(path.join(fixturePath, "multi/multi.txt"))
+  );
+});
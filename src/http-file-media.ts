@@ -0,0 +1,96 @@
+import { Readable } from "stream";
+import type { ReadableStream as WebReadableStream } from "stream/web";
+import { IFileMedia, IReadInterval } from "./interfaces.js";
+
+export interface HttpFileMediaOptions {
+  headers?: Record<string, string>;
+  concurrency?: number;
+  retries?: number;
+  retryDelay?: number;
+}
+
+const wait = (ms: number) => new Promise((resolve) => setTimeout(resolve, ms));
+
+const nameFromUrl = (url: string) => {
+  const { pathname } = new URL(url);
+  return decodeURIComponent(pathname.substring(pathname.lastIndexOf("/") + 1));
+};
+
+export class HttpFileMedia implements IFileMedia {
+  name: string;
+  private active = 0;
+  private queue: (() => void)[] = [];
+  constructor(
+    private url: string,
+    public length: number,
+    private opts: HttpFileMediaOptions = {}
+  ) {
+    this.name = nameFromUrl(url);
+  }
+  static async create(url: string, opts: HttpFileMediaOptions = {}) {
+    const res = await fetch(url, {
+      method: "HEAD",
+      headers: opts.headers ?? {},
+    });
+    const contentLength = res.headers.get("content-length");
+    if (!res.ok || contentLength === null) {
+      throw new Error(`Could not determine length of ${url} (${res.status})`);
+    }
+    return new HttpFileMedia(url, +contentLength, opts);
+  }
+  async createReadStream(interval: IReadInterval) {
+    await this.acquire();
+    try {
+      const res = await this.fetchRange(interval);
+      const stream = Readable.fromWeb(res.body as WebReadableStream);
+      stream.once("close", () => this.release());
+      return stream;
+    } catch (err) {
+      this.release();
+      throw err;
+    }
+  }
+  private async fetchRange({ start, end }: IReadInterval) {
+    const { retries = 3, retryDelay = 500 } = this.opts;
+    for (let attempt = 0; ; attempt++) {
+      let res: Response | undefined;
+      try {
+        res = await fetch(this.url, {
+          headers: { ...this.opts.headers, Range: `bytes=${start}-${end}` },
+        });
+      } catch (err) {
+        if (attempt >= retries) {
+          throw err;
+        }
+      }
+      if (res) {
+        if (res.status === 206 && res.body) {
+          return res;
+        }
+        await res.body?.cancel();
+        if (res.status < 500 || attempt >= retries) {
+          throw new Error(
+            `Range request for ${this.name} failed with status ${res.status}`
+          );
+        }
+      }
+      await wait(retryDelay * 2 ** attempt);
+    }
+  }
+  private acquire() {
+    const { concurrency = 4 } = this.opts;
+    if (this.active < concurrency) {
+      this.active++;
+      return Promise.resolve();
+    }
+    return new Promise<void>((resolve) => this.queue.push(resolve));
+  }
+  private release() {
+    const next = this.queue.shift();
+    if (next) {
+      next();
+    } else {
+      this.active--;
+    }
+  }
+}
@@ -0,0 +1,25 @@
+import { expect, test } from "vitest";
+import { Readable } from "stream";
+
+import { IFileMedia, IReadInterval, RarFilesPackage } from "./index.js";
+import { mockRarArchive } from "./parsing/__mocks__/mock-rar-archive.js";
+
+class ChunkedFileMedia implements IFileMedia {
+  name = "custom.rar";
+  length: number;
+  constructor(private data: Buffer) {
+    this.length = data.length;
+  }
+  createReadStream({ start, end }: IReadInterval) {
+    const bytes = [...this.data.subarray(start, end + 1)];
+    return Readable.from(bytes.map((b) => Buffer.from([b])));
+  }
+}
+
+test("third party file medias can be plugged in through the exported types", async () => {
+  const media = new ChunkedFileMedia(
+    mockRarArchive([{ name: "a.txt", data: Buffer.from("custom media") }])
+  );
+  const [file] = await new RarFilesPackage([media]).parse();
+  expect((await file!.readToEnd()).toString()).toBe("custom media");
+});
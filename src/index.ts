@@ -1,2 +1,65 @@
 export { RarFilesPackage } from "./rar-files-package.js";
+export type { IArchiveIndex } from "./archive-index.js";
+export type {
+  IArchiveStats,
+  IEntrySize,
+  ISizeStats,
+} from "./archive-stats.js";
+export { InnerFile } from "./inner-file.js";
+export { sniffContentType } from "./content-type.js";
+export { createRarStream, rarChunks, writeRar } from "./rar-writer.js";
+export {
+  createTarStream,
+  createZipStream,
+  tarChunks,
+  zipChunks,
+} from "./repackage.js";
+export type { IRepackageEntry } from "./repackage.js";
+export type {
+  IRarChunk,
+  IRarWriterEntry,
+  IRarWriterOpts,
+} from "./rar-writer.js";
+export { RarError } from "./rar-error.js";
+export type { RarErrorCode, RarErrorContext } from "./rar-error.js";
 export { LocalFileMedia } from "./local-file-media.js";
+export { HttpFileMedia } from "./http-file-media.js";
+export type { HttpFileMediaOptions } from "./http-file-media.js";
+export { MemoryFileMedia } from "./memory-file-media.js";
+export { BlobFileMedia } from "./blob-file-media.js";
+export { CachedFileMedia } from "./cached-file-media.js";
+export type { CachedFileMediaOptions } from "./cached-file-media.js";
+export { PartialFileMedia } from "./partial-file-media.js";
+export type { PartialFileMediaOptions } from "./partial-file-media.js";
+export { RateLimiter, ThrottledFileMedia } from "./throttled-file-media.js";
+export type { ThrottledFileMediaOptions } from "./throttled-file-media.js";
+export { Metrics } from "./metrics.js";
+export type { ITreeNode } from "./inner-file-tree.js";
+export type { HostOS, IFileAttributes } from "./file-attributes.js";
+export type {
+  IArchiveHeader,
+  IHeaderVisitor,
+  IMarkerHeader,
+  IRawBlock,
+  ITerminatorHeader,
+} from "./header-visitor.js";
+export type {
+  CompressionMethod,
+  ICompressionInfo,
+} from "./compression-info.js";
+export type {
+  FindOpts,
+  IArchiveInfo,
+  IChunkLocation,
+  IExtractOpts,
+  IExtractProgress,
+  IFileMedia,
+  ILimits,
+  IParseDiagnostic,
+  IProgress,
+  IProgressSink,
+  IReadInterval,
+  IServiceEntry,
+  ITestResult,
+  IVolumeRange,
+} from "./interfaces.js";
@@ -1,15 +1,20 @@
 import { Readable, ReadableOptions } from "stream";
+import { debuglog } from "util";
 import { RarFileChunk } from "./rar-file-chunk.js";
 
+const debug = debuglog("rar-stream");
+
 export class InnerFileStream extends Readable {
   stream?: NodeJS.ReadableStream;
   constructor(
     private rarFileChunks: RarFileChunk[],
-    options?: ReadableOptions
+    options?: ReadableOptions,
+    private onData?: (length: number, chunk: RarFileChunk) => void
   ) {
     super(options);
   }
-  pushData(data: Uint16Array) {
+  pushData(data: Buffer, chunk: RarFileChunk) {
+    this.onData?.(data.length, chunk);
     if (!this.push(data)) {
       this.stream?.pause();
     }
@@ -22,11 +27,29 @@ export class InnerFileStream extends Readable {
 
     if (!chunk) {
       this.push(null);
-    } else {
-      this.stream = await chunk.getStream();
-      this.stream?.on("data", (data) => this.pushData(data));
-      this.stream?.on("end", () => this.next());
+      return;
+    }
+    debug(
+      "streaming bytes %d-%d of volume %d",
+      chunk.startOffset,
+      chunk.endOffset,
+      chunk.volumeIndex
+    );
+    let stream: NodeJS.ReadableStream;
+    try {
+      stream = await chunk.getStream();
+    } catch (error) {
+      this.destroy(error as Error);
+      return;
+    }
+    if (this.destroyed) {
+      destroyStream(stream);
+      return;
     }
+    this.stream = stream;
+    stream.on("data", (data) => this.pushData(data, chunk));
+    stream.on("end", () => this.next());
+    stream.on("error", (error) => this.destroy(error));
   }
   override _read() {
     if (!this.isStarted) {
@@ -35,4 +58,18 @@ export class InnerFileStream extends Readable {
       this.stream?.resume();
     }
   }
+  override _destroy(
+    error: Error | null,
+    callback: (error?: Error | null) => void
+  ) {
+    if (this.stream) {
+      destroyStream(this.stream);
+    }
+    callback(error);
+  }
 }
+
+const destroyStream = (stream: NodeJS.ReadableStream) => {
+  const { destroy } = stream as Partial<Readable>;
+  destroy?.call(stream);
+};
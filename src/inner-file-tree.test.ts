@@ -0,0 +1,81 @@
+import { expect, test } from "vitest";
+
+import { MemoryFileMedia } from "./memory-file-media.js";
+import { mockRarArchive } from "./parsing/__mocks__/mock-rar-archive.js";
+import { RarFilesPackage } from "./rar-files-package.js";
+
+const DIRECTORY = 0xe0;
+
+const newPackage = () =>
+  new RarFilesPackage([
+    new MemoryFileMedia(
+      mockRarArchive([
+        { name: "docs", flags: DIRECTORY, attributes: 0x41ed },
+        { name: "docs\\readme.txt", data: Buffer.from("readme") },
+        { name: "docs/sub/a.txt", data: Buffer.from("a") },
+        { name: "top.txt", data: Buffer.from("top") },
+      ]),
+      "tree.rar"
+    ),
+  ]);
+
+test("inner files expose whether they are directories", async () => {
+  const files = await newPackage().parse();
+  expect(files.map((f) => [f.name, f.isDirectory])).toEqual([
+    ["docs", true],
+    ["docs\\readme.txt", false],
+    ["docs/sub/a.txt", false],
+    ["top.txt", false],
+  ]);
+});
+
+test("rar package tree reconstructs the directory hierarchy", async () => {
+  const root = await newPackage().tree();
+  const describe = (node: typeof root): unknown => ({
+    path: node.path,
+    isDirectory: node.isDirectory,
+    hasFile: !!node.file,
+    children: node.children.map(describe),
+  });
+
+  expect(describe(root)).toEqual({
+    path: "",
+    isDirectory: true,
+    hasFile: false,
+    children: [
+      {
+        path: "docs",
+        isDirectory: true,
+        hasFile: true,
+        children: [
+          {
+            path: "docs/readme.txt",
+            isDirectory: false,
+            hasFile: true,
+            children: [],
+          },
+          {
+            path: "docs/sub",
+            isDirectory: true,
+            hasFile: false,
+            children: [
+              {
+                path: "docs/sub/a.txt",
+                isDirectory: false,
+                hasFile: true,
+                children: [],
+              },
+            ],
+          },
+        ],
+      },
+      { path: "top.txt", isDirectory: false, hasFile: true, children: [] },
+    ],
+  });
+});
+
+test("tree files can be read", async () => {
+  const root = await newPackage().tree();
+  const readme = root.children[0]!.children[0]!.file!;
+  expect((await readme.readToEnd()).toString()).toBe("readme");
+});
@@ -0,0 +1,40 @@
+import { InnerFile } from "./inner-file.js";
+
+export interface ITreeNode {
+  name: string;
+  path: string;
+  isDirectory: boolean;
+  file?: InnerFile;
+  children: ITreeNode[];
+}
+
+export const buildTree = (innerFiles: InnerFile[]) => {
+  const root: ITreeNode = {
+    name: "",
+    path: "",
+    isDirectory: true,
+    children: [],
+  };
+  for (const innerFile of innerFiles) {
+    const parts = innerFile.name.split(/[\\/]/).filter((part) => part);
+    let node = root;
+    parts.forEach((part, i) => {
+      let child = node.children.find((c) => c.name === part);
+      if (!child) {
+        child = {
+          name: part,
+          path: parts.slice(0, i + 1).join("/"),
+          isDirectory: true,
+          children: [],
+        };
+        node.children.push(child);
+      }
+      if (i === parts.length - 1) {
+        child.file = innerFile;
+        child.isDirectory = innerFile.isDirectory;
+      }
+      node = child;
+    });
+  }
+  return root;
+};
@@ -1,8 +1,33 @@
-import { IFileMedia, IReadInterval } from "./interfaces.js";
+import { Readable } from "stream";
+import {
+  FindOpts,
+  IChunkLocation,
+  IFileMedia,
+  IReadInterval,
+  IVolumeRange,
+} from "./interfaces.js";
+import {
+  CachedFileMedia,
+  CachedFileMediaOptions,
+} from "./cached-file-media.js";
+import { describeCompression } from "./compression-info.js";
+import { SNIFF_LENGTH, sniffContentType } from "./content-type.js";
+import { decodeAttributes } from "./file-attributes.js";
 import { InnerFileStream } from "./inner-file-stream.js";
+import { IFileHeader } from "./parsing/file-header-parser.js";
+import { RarError } from "./rar-error.js";
+import { ProgressReporter } from "./progress-reporter.js";
 import { RarFileChunk } from "./rar-file-chunk.js";
+import { safePath } from "./safe-path.js";
 import { streamToBuffer } from "./stream-utils.js";
-import { sum } from "./utils.js";
+import {
+  crc32,
+  dosDateTimeToDate,
+  extendedTimeToDate,
+  sum,
+} from "./utils.js";
+const PEEK_LENGTH = 64 * 1024;
+
 type ChunkMapEntry = {
   index: number;
   start: number;
@@ -12,22 +37,222 @@ type ChunkMapEntry = {
 
 export class InnerFile implements IFileMedia {
   length: number;
+  // set when volumes holding part of the data are missing, in which case
+  // only the data up to the first gap is readable
+  incomplete = false;
+  missingVolumes: string[] = [];
+  // the text of a stored file comment
+  comment?: string;
 
   chunkMap: ChunkMapEntry[];
-  constructor(public name: string, private rarFileChunks: RarFileChunk[]) {
-    this.length = sum(rarFileChunks.map((c) => c.length));
+  private contentType: Promise<string | undefined> | undefined;
+  private session: CachedFileMedia | undefined;
+  constructor(
+    public name: string,
+    private rarFileChunks: RarFileChunk[],
+    readonly fileHead?: IFileHeader,
+    private opts: FindOpts = {}
+  ) {
     this.chunkMap = this.calculateChunkMap(rarFileChunks);
+    this.length = sum([0, ...this.chunkMap.map((c) => c.end - c.start + 1)]);
 
     this.name = name;
   }
+  get isDirectory() {
+    return !!this.fileHead?.isDirectory;
+  }
+  get attributes() {
+    return (
+      this.fileHead &&
+      decodeAttributes(this.fileHead.host, this.fileHead.attributes)
+    );
+  }
+  // The CRC32 stored for the data, unless the data is incomplete
+  get crc32() {
+    return this.incomplete || this.isDirectory
+      ? undefined
+      : this.fileHead?.fileCrc;
+  }
+  get compression() {
+    return this.fileHead && describeCompression(this.fileHead);
+  }
+  // only unix hosts store the link target as the file data
+  get isSymlink() {
+    const attributes = this.attributes;
+    return attributes?.mode !== undefined && attributes.isSymlink;
+  }
+  // Unix symlinks store their target as the file data
+  async linkTarget() {
+    if (!this.isSymlink) {
+      return undefined;
+    }
+    return (await this.readToEnd()).toString("utf-8");
+  }
+  // A relative path that is safe to extract to, see safePath
+  safePath(windows?: boolean) {
+    return safePath(this.name, windows);
+  }
+  // The name as stored in the archive, before decoding
+  get nameBytes() {
+    return this.fileHead?.nameBytes;
+  }
+  get modifiedAt() {
+    const modified = this.fileHead?.extendedTime?.modified;
+    if (modified) {
+      return extendedTimeToDate(modified, this.opts.utcOffset);
+    }
+    return (
+      this.fileHead &&
+      dosDateTimeToDate(this.fileHead.timestamp, this.opts.utcOffset)
+    );
+  }
+  get createdAt() {
+    const created = this.fileHead?.extendedTime?.created;
+    return created && extendedTimeToDate(created, this.opts.utcOffset);
+  }
+  get accessedAt() {
+    const accessed = this.fileHead?.extendedTime?.accessed;
+    return accessed && extendedTimeToDate(accessed, this.opts.utcOffset);
+  }
+  // Guesses the MIME type of audio, video and image files from their first
+  // bytes, which are read once per entry
+  sniffContentType() {
+    this.contentType ??= this.readHead(SNIFF_LENGTH).then(
+      sniffContentType,
+      (error) => {
+        this.contentType = undefined;
+        throw error;
+      }
+    );
+    return this.contentType;
+  }
+  // The first and last bytes, e.g. for media probing, read straight from the
+  // chunks that hold them
+  readHead(length = PEEK_LENGTH) {
+    return this.readRange(0, Math.min(length, this.length) - 1);
+  }
+  readTail(length = PEEK_LENGTH) {
+    return this.readRange(Math.max(this.length - length, 0), this.length - 1);
+  }
+  private async readRange(start: number, end: number) {
+    if (end < start) {
+      return Buffer.alloc(0);
+    }
+    return streamToBuffer(await this.createReadStream({ start, end }));
+  }
   async readToEnd() {
     const stream = await this.createReadStream({ start: 0, end: this.length - 1 });
-    return streamToBuffer(stream);
+    const buffer = await streamToBuffer(stream);
+    if (this.opts.verify) {
+      this.checkCrc(crc32(buffer));
+    }
+    return buffer;
+  }
+  async verify() {
+    if (!this.fileHead || this.isDirectory || !this.fileHead.unpackedSize) {
+      return;
+    }
+    this.checkComplete();
+    const stream = await this.createReadStream({ start: 0, end: this.length - 1 });
+    let crc = 0;
+    for await (const data of stream) {
+      crc = crc32(data, crc);
+    }
+    this.checkCrc(crc);
+  }
+  // The stored CRC32 covers the whole file, so partial data cannot be checked
+  private checkComplete() {
+    if (this.incomplete) {
+      const error = new RarError(
+        "MISSING_VOLUME",
+        this.missingVolumes.length
+          ? `Cannot verify ${this.name}, missing volume ${this.missingVolumes.join(", ")}`
+          : `Cannot verify ${this.name}, it continues past the last volume`,
+        { entry: this.name }
+      );
+      this.opts.metrics?.addError(error);
+      throw error;
+    }
+  }
+  private checkCrc(actual: number) {
+    this.checkComplete();
+    if (this.fileHead && !this.isDirectory && this.fileHead.fileCrc !== actual) {
+      const hex = (crc: number) => crc.toString(16).padStart(8, "0");
+      const error = new RarError(
+        "CHECKSUM_MISMATCH",
+        `Checksum mismatch for ${this.name}: expected ${hex(this.fileHead.fileCrc)}, got ${hex(actual)}`,
+        { entry: this.name }
+      );
+      this.opts.metrics?.addError(error);
+      throw error;
+    }
+  }
+  async stream(interval: IReadInterval = { start: 0, end: this.length - 1 }) {
+    return Readable.toWeb(await this.createReadStream(interval));
+  }
+  // Where the byte at offset and the rest of its chunk are stored.
+  locate(offset: number): IChunkLocation {
+    if (offset < 0 || offset >= this.length) {
+      throw Error("Illegal offset");
+    }
+    const { start, end, chunk } = this.findMappedChunk(offset);
+    return {
+      volume: chunk.fileMedia,
+      volumeIndex: chunk.volumeIndex,
+      fileOffset: offset,
+      offset: chunk.startOffset + offset - start,
+      length: end - offset + 1,
+    };
+  }
+  *locations(): Generator<IChunkLocation> {
+    for (const { start, end, chunk } of this.chunkMap) {
+      yield {
+        volume: chunk.fileMedia,
+        volumeIndex: chunk.volumeIndex,
+        fileOffset: start,
+        offset: chunk.startOffset,
+        length: end - start + 1,
+      };
+    }
+  }
+  // The volumes, in order, and the inclusive ranges interval is read from.
+  requiredVolumes(
+    interval: IReadInterval = { start: 0, end: this.length - 1 }
+  ): IVolumeRange[] {
+    const { start, end } = interval;
+    if (start < 0 || end >= this.length) {
+      throw Error("Illegal start/end offset");
+    }
+    if (end < start) {
+      return [];
+    }
+    return this.getChunksToStream(start, end).map((chunk) => ({
+      volume: chunk.fileMedia,
+      volumeIndex: chunk.volumeIndex,
+      start: chunk.startOffset,
+      end: chunk.endOffset,
+    }));
+  }
+  // One block cache for every reader of this entry, so clients streaming it
+  // concurrently at different offsets share their reads. The options only
+  // apply to the first call.
+  share(opts?: CachedFileMediaOptions) {
+    this.session ??= new CachedFileMedia(this, opts);
+    return this.session;
+  }
+  // Warms the volumes that support prefetching, such as CachedFileMedia,
+  // for a range that will be read soon, e.g. ahead of a seek
+  async prefetch(interval: IReadInterval) {
+    await Promise.all(
+      this.requiredVolumes(interval).map(({ volume, start, end }) =>
+        volume.prefetch?.({ start, end })
+      )
+    );
   }
   getChunksToStream(fileStart: number, fileEnd: number) {
     const { index: startIndex, start: startOffset } =
       this.findMappedChunk(fileStart);
-    let { index: endIndex, end: endOffset } = this.findMappedChunk(fileEnd);
+    const { index: endIndex, end: endOffset } = this.findMappedChunk(fileEnd);
 
     const chunksToStream = this.rarFileChunks.slice(startIndex, endIndex + 1);
 
@@ -37,13 +262,7 @@ export class InnerFile implements IFileMedia {
       Math.abs(startOffset - fileStart)
     );
 
-    let diff = Math.abs(endOffset - fileEnd);
-    if (diff === this.rarFileChunks.length) {
-      diff = 0;
-    }
-    if (diff !== 0) {
-      chunksToStream[last] = chunksToStream[last]!.padEnd(diff);
-    }
+    chunksToStream[last] = chunksToStream[last]!.padEnd(endOffset - fileEnd);
 
     return chunksToStream;
   }
@@ -51,23 +270,42 @@ export class InnerFile implements IFileMedia {
     if (!interval) {
       interval = { start: 0, end: this.length - 1 };
     }
-    let { start, end } = interval;
+    const { start, end } = interval;
 
     if (start < 0 || end >= this.length) {
       throw Error("Illegal start/end offset");
     }
+    if (end < start) {
+      return Promise.resolve(new InnerFileStream([]));
+    }
 
-    return Promise.resolve(
-      new InnerFileStream(this.getChunksToStream(start, end))
+    const { progress, progressInterval, metrics } = this.opts;
+    if (!progress && !metrics) {
+      return Promise.resolve(
+        new InnerFileStream(this.getChunksToStream(start, end))
+      );
+    }
+    const reporter =
+      progress && new ProgressReporter(this.name, progress, progressInterval);
+    const stream = new InnerFileStream(
+      this.getChunksToStream(start, end),
+      undefined,
+      (length, chunk) => {
+        reporter?.add(length, chunk.volumeIndex);
+        metrics?.addEntryBytes(this.name, length);
+      }
     );
+    stream.on("end", () => reporter?.flush());
+    return Promise.resolve(stream);
   }
   calculateChunkMap(rarFileChunks: RarFileChunk[]) {
     const chunkMap: ChunkMapEntry[] = [];
     let index = 0;
     let fileOffset = 0;
     for (const chunk of rarFileChunks) {
+      // chunk offsets are inclusive, so a chunk spans end - start + 1 bytes
       const start = fileOffset;
-      const end = fileOffset + chunk.length;
+      const end = fileOffset + chunk.endOffset - chunk.startOffset;
       fileOffset = end + 1;
 
       chunkMap.push({ index, start, end, chunk });
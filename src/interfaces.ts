@@ -1,3 +1,6 @@
+import { ICompressionInfo } from "./compression-info.js";
+import type { InnerFile } from "./inner-file.js";
+import type { Metrics } from "./metrics.js";
 import { ArchiveHeaderParser } from "./parsing/archive-header-parser.js";
 import { FileHeaderParser } from "./parsing/file-header-parser.js";
 import { MarkerHeaderParser } from "./parsing/marker-header-parser.js";
@@ -6,17 +9,112 @@ export interface IFileMedia {
   length: number;
   name: string;
   createReadStream(opts?: IReadInterval): Promise<NodeJS.ReadableStream> | NodeJS.ReadableStream;
+  // optional hint that the interval will be read soon
+  prefetch?(interval: IReadInterval): Promise<void>;
 }
 export interface IReadInterval {
   start: number;
   end: number;
 }
+export interface IChunkLocation {
+  volume: IFileMedia;
+  volumeIndex: number;
+  // offset of the first byte within the inner file and within the volume
+  fileOffset: number;
+  offset: number;
+  length: number;
+}
+export interface IVolumeRange extends IReadInterval {
+  volume: IFileMedia;
+  volumeIndex: number;
+}
 export interface FindOpts {
-  filter(
+  filter?(
     filename: string,
-    idx: number
+    idx: number,
+    compression: ICompressionInfo
   ): boolean;
-  maxFiles: number;
+  maxFiles?: number;
+  verify?: boolean;
+  progress?: IProgressSink;
+  progressInterval?: number;
+  limits?: ILimits;
+  tolerant?: boolean;
+  maxScanBytes?: number;
+  includeServiceEntries?: boolean;
+  nameEncoding?: string | ((nameBytes: Buffer) => string);
+  utcOffset?: number;
+  validateVolumes?: boolean;
+  allowMissingVolumes?: boolean;
+  metrics?: Metrics;
+}
+export interface ITestResult {
+  name: string;
+  ok: boolean;
+  error?: Error;
+}
+export interface IParseDiagnostic {
+  volume: string;
+  offset: number;
+  message: string;
+}
+export interface ILimits {
+  maxNameLength?: number;
+  maxHeaderSize?: number;
+  maxFileCount?: number;
+  maxUnpackedSize?: number;
+}
+export interface IProgress {
+  name: string;
+  volumeIndex: number;
+  bytesRead: number;
+  bytesWritten: number;
+}
+export interface IProgressSink {
+  onProgress(progress: IProgress): void;
+}
+
+export interface IExtractProgress {
+  name: string;
+  fileBytes: number;
+  fileLength: number;
+  bytes: number;
+  totalBytes: number;
+}
+export interface IExtractOpts extends FindOpts {
+  windowsSafeNames?: boolean;
+  symlinks?: "skip" | "link" | "dereference";
+  includeStreams?: boolean;
+  preservePermissions?: boolean;
+  onProgress?(progress: IExtractProgress): void;
+}
+
+export interface IArchiveInfo {
+  isMultiVolume: boolean;
+  isSolid: boolean;
+  isLocked: boolean;
+  hasRecovery: boolean;
+  hasComment: boolean;
+  // the text of a stored archive comment
+  comment?: string;
+  hasEncryptedHeaders: boolean;
+  isNewNameScheme: boolean;
+  volumeCount: number;
+  sfxStubSize: number;
+}
+
+export interface IServiceEntry {
+  type: string;
+  volume: string;
+  volumeIndex: number;
+  offset: number;
+  packedSize: number;
+  unpackedSize: number;
+  method: number;
+  // the file header the block follows, which owns e.g. its STM streams
+  owner?: string;
+  streamName?: string;
+  file?: InnerFile;
 }
 
 export type IParsers =
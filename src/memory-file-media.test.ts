@@ -0,0 +1,47 @@
+import { expect, test } from "vitest";
+import path from "path";
+import fs from "fs";
+
+import { MemoryFileMedia } from "./memory-file-media.js";
+import { RarFilesPackage } from "./rar-files-package.js";
+import { streamToBuffer } from "./stream-utils.js";
+
+const fixturePath = path.resolve(__dirname, "./__fixtures__");
+
+test("MemoryFileMedia should have the length of its buffer", () => {
+  const media = new MemoryFileMedia(Buffer.from("0123456789"), "digits");
+  expect(media.name).toBe("digits");
+  expect(media.length).toBe(10);
+});
+
+test("MemoryFileMedia#createReadStream should return an inclusive byte range", async () => {
+  const media = new MemoryFileMedia(Buffer.from("0123456789"), "digits");
+  const buffer = await streamToBuffer(
+    await media.createReadStream({ start: 2, end: 5 })
+  );
+  expect(buffer.toString()).toBe("2345");
+});
+
+test("MemoryFileMedia should accept a Uint8Array view", async () => {
+  const bytes = new Uint8Array([0, 1, 2, 3, 4, 5]).subarray(2);
+  const media = new MemoryFileMedia(bytes, "view");
+  const buffer = await streamToBuffer(
+    await media.createReadStream({ start: 0, end: 3 })
+  );
+  expect(buffer).toEqual(Buffer.from([2, 3, 4, 5]));
+});
+
+test("rar package can parse volumes held in memory", async () => {
+  const medias = ["multi.rar", "multi.r00", "multi.r01"].map(
+    (name) =>
+      new MemoryFileMedia(
+        fs.readFileSync(path.join(fixturePath, "multi", name)),
+        name
+      )
+  );
+  const [file] = await new RarFilesPackage(medias).parse();
+  const content = await file!.readToEnd();
+  expect(content).toEqual(
+    fs.readFileSync(path.join(fixturePath, "multi/multi.txt"))
+  );
+});
@@ -0,0 +1,19 @@
+import { Readable } from "stream";
+import { IFileMedia, IReadInterval } from "./interfaces.js";
+
+export class MemoryFileMedia implements IFileMedia {
+  length: number;
+  private buffer: Buffer;
+  constructor(buffer: Uint8Array, public name: string) {
+    this.buffer = Buffer.from(buffer.buffer, buffer.byteOffset, buffer.length);
+    this.length = buffer.length;
+  }
+  createReadStream(interval: IReadInterval) {
+    const { start, end } = interval;
+    return Promise.resolve(
+      Readable.from([this.buffer.subarray(start, end + 1)], {
+        objectMode: false,
+      })
+    );
+  }
+}
@@ -0,0 +1,51 @@
+import { expect, test } from "vitest";
+
+import { CachedFileMedia } from "./cached-file-media.js";
+import { MemoryFileMedia } from "./memory-file-media.js";
+import { Metrics } from "./metrics.js";
+import { mockRarArchive } from "./parsing/__mocks__/mock-rar-archive.js";
+import { RarError } from "./rar-error.js";
+import { RarFilesPackage } from "./rar-files-package.js";
+import { streamToBuffer } from "./stream-utils.js";
+
+test("Metrics count cache hits and misses", async () => {
+  const metrics = new Metrics();
+  const media = new CachedFileMedia(
+    new MemoryFileMedia(Buffer.from("0123456789"), "digits"),
+    { blockSize: 4, readAhead: 0, metrics }
+  );
+  expect(metrics.cacheHitRatio).toBe(0);
+  await streamToBuffer(await media.createReadStream({ start: 0, end: 5 }));
+  await streamToBuffer(await media.createReadStream({ start: 2, end: 9 }));
+  expect([metrics.cacheHits, metrics.cacheMisses]).toEqual([2, 3]);
+  expect(metrics.cacheHitRatio).toBe(0.4);
+});
+
+test("Metrics count parses and errors by code", async () => {
+  const metrics = new Metrics();
+  const archive = mockRarArchive([{ name: "a.txt", data: Buffer.from("hi") }]);
+  const [file] = await new RarFilesPackage([
+    new MemoryFileMedia(archive, "test.rar"),
+  ]).parse({ metrics });
+  await expect(
+    new RarFilesPackage([
+      new MemoryFileMedia(Buffer.from("not a rar"), "bad.rar"),
+    ]).parse({ metrics })
+  ).rejects.toThrow(RarError);
+  expect(metrics.parses).toBe(2);
+  expect(metrics.parseTime).toBeGreaterThan(0);
+  expect(metrics.errors.get("NO_SIGNATURE")).toBe(1);
+
+  const corrupt = Buffer.from(archive);
+  corrupt[archive.indexOf("hi")] = 0;
+  const [broken] = await new RarFilesPackage([
+    new MemoryFileMedia(corrupt, "test.rar"),
+  ]).parse({ metrics });
+  await expect(broken!.verify()).rejects.toThrow("Checksum mismatch");
+  await file!.verify();
+  expect(metrics.snapshot()).toMatchObject({
+    parses: 3,
+    entries: { "a.txt": 4 },
+    errors: { NO_SIGNATURE: 1, CHECKSUM_MISMATCH: 1 },
+  });
+});
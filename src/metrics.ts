@@ -0,0 +1,68 @@
+import { RarError } from "./rar-error.js";
+
+// Counters for monitoring long running servers. Volumes are counted by
+// ThrottledFileMedia, cache lookups by CachedFileMedia, and entries, parses
+// and errors by a package parsed with the metrics option. Nothing is counted
+// where no Metrics is passed.
+export class Metrics {
+  bytesRead = 0;
+  readonly volumes = new Map<string, number>();
+  readonly entries = new Map<string, number>();
+  cacheHits = 0;
+  cacheMisses = 0;
+  parses = 0;
+  // milliseconds
+  parseTime = 0;
+  // by RarError code, other errors count as ERROR
+  readonly errors = new Map<string, number>();
+  get cacheHitRatio() {
+    const lookups = this.cacheHits + this.cacheMisses;
+    return lookups ? this.cacheHits / lookups : 0;
+  }
+  addVolumeBytes(name: string, length: number) {
+    this.bytesRead += length;
+    this.volumes.set(name, (this.volumes.get(name) ?? 0) + length);
+  }
+  addEntryBytes(name: string, length: number) {
+    this.entries.set(name, (this.entries.get(name) ?? 0) + length);
+  }
+  addCacheLookup(hit: boolean) {
+    if (hit) {
+      this.cacheHits++;
+    } else {
+      this.cacheMisses++;
+    }
+  }
+  addParse(time: number) {
+    this.parses++;
+    this.parseTime += time;
+  }
+  addError(error: unknown) {
+    const code = error instanceof RarError ? error.code : "ERROR";
+    this.errors.set(code, (this.errors.get(code) ?? 0) + 1);
+  }
+  // A JSON serializable copy of the counters, e.g. for an exporter
+  snapshot() {
+    return {
+      bytesRead: this.bytesRead,
+      volumes: Object.fromEntries(this.volumes),
+      entries: Object.fromEntries(this.entries),
+      cacheHits: this.cacheHits,
+      cacheMisses: this.cacheMisses,
+      cacheHitRatio: this.cacheHitRatio,
+      parses: this.parses,
+      parseTime: this.parseTime,
+      errors: Object.fromEntries(this.errors),
+    };
+  }
+  reset() {
+    this.bytesRead = 0;
+    this.volumes.clear();
+    this.entries.clear();
+    this.cacheHits = 0;
+    this.cacheMisses = 0;
+    this.parses = 0;
+    this.parseTime = 0;
+    this.errors.clear();
+  }
+}
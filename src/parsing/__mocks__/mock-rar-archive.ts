@@ -0,0 +1,105 @@
+import { crc32 } from "../../utils.js";
+
+export interface IMockRarEntry {
+  name: string;
+  nameBytes?: Buffer;
+  data?: Buffer;
+  flags?: number;
+  host?: number;
+  attributes?: number;
+  timestamp?: number;
+  method?: number;
+  version?: number;
+  type?: number;
+  // extra header bytes after the name, e.g. the name of an STM stream
+  subData?: Buffer;
+  // packed and unpacked size written instead of the data length, so large
+  // entries can be described without their data
+  size?: number;
+  // writes the high size words, implied by sizes of 4 GiB and more
+  highSize?: boolean;
+}
+
+const withHeaderCrc = (header: Buffer) => {
+  header.writeUInt16LE(crc32(header.subarray(2)) & 0xffff, 0);
+  return header;
+};
+
+const RAR4_SIGNATURE = Buffer.from("526172211a0700", "hex");
+
+const mainHeader = (flags: number, comment?: Buffer) => {
+  const header = Buffer.concat([
+    Buffer.alloc(13),
+    comment ? commentHeader(comment) : Buffer.alloc(0),
+  ]);
+  header.writeUInt8(0x73, 2);
+  header.writeUInt16LE(comment ? flags | 0x02 : flags, 3);
+  header.writeUInt16LE(header.length, 5);
+  return withHeaderCrc(header);
+};
+
+// the comment blocks of RAR 2.x hold their text in the header
+const commentHeader = (text: Buffer, method = 0x30) => {
+  const header = Buffer.alloc(13 + text.length);
+  header.writeUInt8(0x75, 2);
+  header.writeUInt16LE(header.length, 5);
+  header.writeUInt16LE(text.length, 7);
+  header.writeUInt8(20, 9);
+  header.writeUInt8(method, 10);
+  header.writeUInt16LE(crc32(text) & 0xffff, 11);
+  text.copy(header, 13);
+  return withHeaderCrc(header);
+};
+
+const fileHeader = (entry: IMockRarEntry) => {
+  if (entry.type === 0x75) {
+    return commentHeader(entry.data ?? Buffer.alloc(0), entry.method);
+  }
+  const data = entry.data ?? Buffer.alloc(0);
+  const name = entry.nameBytes ?? Buffer.from(entry.name, "utf-8");
+  const subData = entry.subData ?? Buffer.alloc(0);
+  const size = entry.size ?? data.length;
+  const highSize = entry.highSize ?? size >= 0x100000000;
+  const nameOffset = highSize ? 40 : 32;
+  const header = Buffer.alloc(nameOffset + name.length + subData.length);
+  header.writeUInt8(entry.type ?? 0x74, 2);
+  header.writeUInt16LE((entry.flags ?? 0) | 0x8000 | (highSize ? 0x100 : 0), 3);
+  header.writeUInt16LE(header.length, 5);
+  header.writeUInt32LE(size % 0x100000000, 7);
+  header.writeUInt32LE(size % 0x100000000, 11);
+  header.writeUInt8(entry.host ?? 3, 15);
+  header.writeUInt32LE(crc32(data), 16);
+  header.writeUInt32LE(entry.timestamp ?? 0, 20);
+  header.writeUInt8(entry.version ?? 20, 24);
+  header.writeUInt8(entry.method ?? 0x30, 25);
+  header.writeUInt16LE(name.length, 26);
+  header.writeUInt32LE(entry.attributes ?? 0x81a4, 28);
+  if (highSize) {
+    header.writeUInt32LE(Math.floor(size / 0x100000000), 32);
+    header.writeUInt32LE(Math.floor(size / 0x100000000), 36);
+  }
+  name.copy(header, nameOffset);
+  subData.copy(header, nameOffset + name.length);
+  return Buffer.concat([withHeaderCrc(header), data]);
+};
+
+const endHeader = () => {
+  const header = Buffer.alloc(7);
+  header.writeUInt8(0x7b, 2);
+  header.writeUInt16LE(0x4000, 3);
+  header.writeUInt16LE(header.length, 5);
+  return withHeaderCrc(header);
+};
+
+// an archive comment is embedded in the main header like RAR 2.x does
+export const mockRarArchive = (
+  entries: IMockRarEntry[],
+  archiveFlags = 0,
+  archiveComment?: Buffer
+) =>
+  Buffer.concat([
+    RAR4_SIGNATURE,
+    mainHeader(archiveFlags, archiveComment),
+    ...entries.map(fileHeader),
+    endHeader(),
+  ]);
@@ -252,6 +252,14 @@ test("FileHeaderParser.parse should parse hasExtendedTime flag", () => {
   expect(parseHeader("hasExtendedTime", "00")).toBeFalsy();
 });
 
+test("FileHeaderParser.parse should parse isDirectory from the window bits", () => {
+  const padding = newPadding(3);
+  expect(parseHeader("isDirectory", padding + "e000")).toBeTruthy();
+  expect(parseHeader("isDirectory", padding + "e010")).toBeTruthy();
+  expect(parseHeader("isDirectory", padding + "c000")).toBeFalsy();
+  expect(parseHeader("isDirectory", padding + "0000")).toBeFalsy();
+});
+
 test("FileHeaderParser.parse should handle high file size", () => {
   const data =
     "D97774111111115C1000005C10000003C5A6D2158A5" +
@@ -270,3 +278,30 @@ test("FileHeaderParser.parse should parse name properly", () => {
     "E6F772E74787400C0";
   expect(parseHeader("name", data)).toBe("acknow.txt");
 });
+
+test("FileHeaderParser.parse should parse extended times after the name", () => {
+  const header = Buffer.alloc(FileHeaderParser.HEADER_SIZE);
+  header.writeUInt16LE(0x1000, 3);
+  header.writeUInt32LE(0x495a9ad3, 20);
+  header.writeUInt16LE(1, 26);
+  header.write("a", 32);
+  // mtime with 1 extra second and 3 bytes of ticks, ctime without ticks
+  header.writeUInt16LE(0xf800, 33);
+  header.writeUIntLE(0x123456, 35, 3);
+  header.writeUInt32LE(0x495a9ad0, 38);
+
+  expect(new FileHeaderParser(header).parse().extendedTime).toEqual({
+    modified: { timestamp: 0x495a9ad3, ticks: 10_000_000 + 0x123456 },
+    created: { timestamp: 0x495a9ad0, ticks: 0 },
+  });
+});
+
+test("FileHeaderParser.parse should read the high size words unsigned", () => {
+  const header = Buffer.alloc(FileHeaderParser.HEADER_SIZE);
+  header.writeUInt16LE(0x100, 3);
+  header.writeUInt32LE(0x80000000, 32);
+  header.writeUInt32LE(0xffffffff, 36);
+  const { size, unpackedSize } = new FileHeaderParser(header).parse();
+  expect(size).toBe(0x80000000 * 0x100000000);
+  expect(unpackedSize).toBe(0xffffffff * 0x100000000);
+});
@@ -1,3 +1,5 @@
+import { decodeUnicodeName } from "./unicode-name.js";
+
 interface IFileHeaderFlags {
   continuesFromPrevious: boolean;
   continuesInNext: boolean;
@@ -9,6 +11,19 @@ interface IFileHeaderFlags {
   hasSalt: boolean;
   isOldVersion: boolean;
   hasExtendedTime: boolean;
+  isDirectory: boolean;
+}
+
+// A DOS timestamp plus the 100ns ticks beyond its 2 second precision
+export interface IExtendedTime {
+  timestamp: number;
+  ticks: number;
+}
+export interface IExtendedTimes {
+  modified?: IExtendedTime;
+  created?: IExtendedTime;
+  accessed?: IExtendedTime;
+  archived?: IExtendedTime;
 }
 
 interface IFileHeaderRaw {
@@ -26,6 +41,8 @@ interface IFileHeaderRaw {
   nameSize: number;
   attributes: number;
   name: string;
+  nameBytes: Buffer;
+  extendedTime?: IExtendedTimes;
 }
 
 export type IFileHeader = IFileHeaderRaw & IFileHeaderFlags;
@@ -35,19 +52,49 @@ export class FileHeaderParser {
   constructor(private buffer: Buffer) {}
   private handleHighFileSize(parsedVars: IFileHeader) {
     if (parsedVars.hasHighSize) {
-      const highPackSize = this.buffer.readInt32LE(this.offset);
+      const highPackSize = this.buffer.readUInt32LE(this.offset);
       this.offset += 4;
-      const highUnpackSize = this.buffer.readInt32LE(this.offset);
+      const highUnpackSize = this.buffer.readUInt32LE(this.offset);
       this.offset += 4;
       parsedVars.size = highPackSize * 0x100000000 + parsedVars.size;
       parsedVars.unpackedSize =
         highUnpackSize * 0x100000000 + parsedVars.unpackedSize;
     }
   }
-  private parseFileName(parsedVars: IFileHeaderRaw) {
-    parsedVars.name = this.buffer
-      .subarray(this.offset, this.offset + parsedVars.nameSize)
-      .toString("utf-8");
+  private parseFileName(parsedVars: IFileHeader) {
+    const nameBytes = this.buffer.subarray(
+      this.offset,
+      this.offset + parsedVars.nameSize
+    );
+    parsedVars.nameBytes = Buffer.from(nameBytes);
+    parsedVars.name = parsedVars.hasSpecialName
+      ? decodeUnicodeName(nameBytes)
+      : nameBytes.toString("utf-8");
+  }
+  private parseExtendedTime(parsedVars: IFileHeader) {
+    const kinds = ["modified", "created", "accessed", "archived"] as const;
+    const extendedTime: IExtendedTimes = {};
+    const flags = this.buffer.readUInt16LE(this.offset);
+    this.offset += 2;
+    kinds.forEach((kind, index) => {
+      const mode = flags >> ((3 - index) * 4);
+      if (!(mode & 8)) {
+        return;
+      }
+      let timestamp = parsedVars.timestamp;
+      if (kind !== "modified") {
+        timestamp = this.buffer.readUInt32LE(this.offset);
+        this.offset += 4;
+      }
+      let ticks = mode & 4 ? 10_000_000 : 0;
+      const count = mode & 3;
+      for (let i = 0; i < count; i++) {
+        ticks += this.buffer.readUInt8(this.offset) << ((i + 3 - count) * 8);
+        this.offset += 1;
+      }
+      extendedTime[kind] = { timestamp, ticks };
+    });
+    parsedVars.extendedTime = extendedTime;
   }
   private parseFlags(parsedVars: IFileHeaderRaw): IFileHeaderFlags {
     return {
@@ -61,6 +108,7 @@ export class FileHeaderParser {
       hasSalt: (parsedVars.flags & 0x400) !== 0,
       isOldVersion: (parsedVars.flags & 0x800) !== 0,
       hasExtendedTime: (parsedVars.flags & 0x1000) !== 0,
+      isDirectory: (parsedVars.flags & 0xe0) === 0xe0,
     };
   }
   parse(): IFileHeader {
@@ -118,12 +166,17 @@ export class FileHeaderParser {
       nameSize,
       attributes,
       name: "",
+      nameBytes: Buffer.alloc(0),
     };
 
     const boolFlags = this.parseFlags(vars);
     const header = { ...vars, ...boolFlags };
     this.handleHighFileSize(header);
     this.parseFileName(header);
+    if (header.hasExtendedTime) {
+      this.offset += nameSize + (header.hasSalt ? 8 : 0);
+      this.parseExtendedTime(header);
+    }
     this.offset = 0;
     return header;
   }
@@ -52,3 +52,9 @@ test("MarkerHeaderParser.parse should parse add_size flag", () => {
   expect(parseHeader("size", "526172219A07000A000000")).toBe(0x11);
   expect(parseHeader("size", "526172219A0700F8FFFFFF")).toBe(0xffffffff);
 });
+
+test("MarkerHeaderParser.parse should recognise the RAR 4 signature", () => {
+  expect(parseHeader("isValid", "526172211a0700")).toBe(true);
+  expect(parseHeader("isValid", "526172211a070100")).toBe(false);
+  expect(parseHeader("isValid", "4d5a90000300000004")).toBe(false);
+});
@@ -11,6 +11,9 @@ export class MarkerHeaderParser {
       let addSize = this.headerBuffer.readUint32LE(7);
       size += addSize || 0;
     }
-    return { crc, type, flags, size };
+    // "Rar!\x1a\x07\x00" read as a block header
+    const isValid =
+      crc === 0x6152 && type === 0x72 && flags === 0x1a21 && size === 7;
+    return { crc, type, flags, size, isValid };
   }
 }
@@ -64,3 +64,23 @@ test("TerminatorHeaderParser.parse should parse size as little endian", () => {
   expect(parseHeader("size", "ffffffffff1234")).toBe(0x3412);
   expect(parseHeader("size", "ffffffffff3412")).toBe(0x1234);
 });
+
+test("TerminatorHeaderParser.parse should parse flags as booleans", () => {
+  expect(parseHeader("hasNextVolume", "ffffff0100")).toBe(true);
+  expect(parseHeader("hasDataCrc", "ffffff0200")).toBe(true);
+  expect(parseHeader("hasRevSpace", "ffffff0400")).toBe(true);
+  expect(parseHeader("hasVolumeNumber", "ffffff0800")).toBe(true);
+  expect(parseHeader("hasNextVolume", "ffffff0e00")).toBe(false);
+});
+
+test("TerminatorHeaderParser.parse should parse volume number after data crc", () => {
+  expect(parseHeader("dataCrc", "ffffff0a0014001234567801000000")).toBe(
+    0x78563412
+  );
+  expect(parseHeader("volumeNumber", "ffffff0a0014001234567801000000")).toBe(1);
+});
+
+test("TerminatorHeaderParser.parse should parse volume number without data crc", () => {
+  expect(parseHeader("dataCrc", "ffffff08000900ff00")).toBe(undefined);
+  expect(parseHeader("volumeNumber", "ffffff08000900ff00")).toBe(0xff);
+});
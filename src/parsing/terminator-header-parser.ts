@@ -1,3 +1,20 @@
+interface ITerminatorHeader {
+  crc: number;
+  type: number;
+  flags: number;
+  size: number;
+  dataCrc?: number;
+  volumeNumber?: number;
+}
+
+function parseFlags(parsedVars: ITerminatorHeader) {
+  return {
+    hasNextVolume: (parsedVars.flags & 0x0001) !== 0,
+    hasDataCrc: (parsedVars.flags & 0x0002) !== 0,
+    hasRevSpace: (parsedVars.flags & 0x0004) !== 0,
+    hasVolumeNumber: (parsedVars.flags & 0x0008) !== 0,
+  };
+}
 export class TerminatorHeaderParser {
   static HEADER_SIZE = 27;
   constructor(private headerBuffer: Buffer) {}
@@ -6,6 +23,17 @@ export class TerminatorHeaderParser {
     const type = this.headerBuffer.readUInt8(2);
     const flags = this.headerBuffer.readUInt16LE(3);
     const size = this.headerBuffer.readUInt16LE(5);
-    return { crc, type, flags, size };
+    const vars: ITerminatorHeader = { crc, type, flags, size };
+    const boolFlags = parseFlags(vars);
+
+    let offset = 7;
+    if (boolFlags.hasDataCrc) {
+      vars.dataCrc = this.headerBuffer.readUInt32LE(offset);
+      offset += 4;
+    }
+    if (boolFlags.hasVolumeNumber) {
+      vars.volumeNumber = this.headerBuffer.readUInt16LE(offset);
+    }
+    return { ...boolFlags, ...vars };
   }
 }
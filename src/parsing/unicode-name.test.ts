@@ -0,0 +1,30 @@
+import { expect, test } from "vitest";
+import { decodeUnicodeName } from "./unicode-name.js";
+
+const decode = (hex: string) => decodeUnicodeName(Buffer.from(hex, "hex"));
+
+test("decodeUnicodeName treats names without a separator as utf-8", () => {
+  expect(decodeUnicodeName(Buffer.from("påske/ünï.txt", "utf-8"))).toBe(
+    "påske/ünï.txt"
+  );
+});
+
+test("decodeUnicodeName combines low bytes with the high byte", () => {
+  // "?a\0", high byte 0x03, opcodes 1 (0xa9 + high byte) and 0 (0x61)
+  expect(decode("3f6100" + "03" + "40" + "a961")).toBe("Ωa");
+});
+
+test("decodeUnicodeName reads full utf-16 units", () => {
+  // "?\0", high byte 0, opcode 2 with 0x20ac
+  expect(decode("3f00" + "00" + "80" + "ac20")).toBe("€");
+});
+
+test("decodeUnicodeName copies runs of the ascii name", () => {
+  // "abc.txt\0", opcode 3 copying 3 bytes, then opcode 0 for "!"
+  expect(decode("6162632e74787400" + "00" + "c0" + "01" + "21")).toBe("abc!");
+});
+
+test("decodeUnicodeName corrects copied runs with the high byte", () => {
+  // "\x10\x11\x12\0", high byte 0x04, opcode 3 copying 3 bytes below it
+  expect(decode("10111200" + "04" + "c0" + "81" + "00")).toBe("АБВ");
+});
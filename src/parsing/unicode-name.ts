@@ -0,0 +1,57 @@
+// RAR 3.x and 4.x store unicode names as "ascii\0encoded". The encoded part
+// is a high byte followed by 2 bit opcodes that either give a UTF-16 unit
+// directly or reuse bytes of the ascii part. Without the separator the
+// whole name is UTF-8.
+export function decodeUnicodeName(nameBytes: Buffer) {
+  const separator = nameBytes.indexOf(0);
+  if (separator === -1) {
+    return nameBytes.toString("utf-8");
+  }
+  const name = nameBytes.subarray(0, separator);
+  const encoded = nameBytes.subarray(separator + 1);
+  const units: number[] = [];
+  let position = 0;
+  const next = () => encoded[position++] ?? 0;
+  const highByte = next();
+  let flags = 0;
+  let flagBits = 0;
+  while (position < encoded.length) {
+    if (flagBits === 0) {
+      flags = next();
+      flagBits = 8;
+    }
+    switch (flags >> 6) {
+      case 0:
+        units.push(next());
+        break;
+      case 1:
+        units.push(next() + (highByte << 8));
+        break;
+      case 2:
+        units.push(next() + (next() << 8));
+        break;
+      default: {
+        let length = next();
+        if (length & 0x80) {
+          const correction = next();
+          for (length = (length & 0x7f) + 2; length > 0; length--) {
+            if (units.length >= name.length) {
+              break;
+            }
+            units.push(((name[units.length]! + correction) & 0xff) + (highByte << 8));
+          }
+        } else {
+          for (length += 2; length > 0; length--) {
+            if (units.length >= name.length) {
+              break;
+            }
+            units.push(name[units.length]!);
+          }
+        }
+      }
+    }
+    flags = (flags << 2) & 0xff;
+    flagBits -= 2;
+  }
+  return String.fromCharCode(...units);
+}
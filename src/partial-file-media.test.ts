@@ -0,0 +1,109 @@
+import { expect, test } from "vitest";
+
+import { IReadInterval } from "./interfaces.js";
+import { MemoryFileMedia } from "./memory-file-media.js";
+import { PartialFileMedia } from "./partial-file-media.js";
+import { mockRarArchive } from "./parsing/__mocks__/mock-rar-archive.js";
+import { RarFilesPackage } from "./rar-files-package.js";
+import { streamToBuffer } from "./stream-utils.js";
+
+type Listener = (error?: Error) => void;
+
+// pretends to download the buffer from the start, notifying on each piece
+const newDownload = (buffer: Buffer) => {
+  let downloaded = 0;
+  const listeners = new Set<Listener>();
+  return {
+    isAvailable: ({ end }: IReadInterval) => end < downloaded,
+    onChange(listener: Listener) {
+      listeners.add(listener);
+      return () => listeners.delete(listener);
+    },
+    receive(bytes: number) {
+      downloaded = Math.min(buffer.length, downloaded + bytes);
+      listeners.forEach((l) => l());
+    },
+    fail(error: Error) {
+      listeners.forEach((l) => l(error));
+    },
+    get listenerCount() {
+      return listeners.size;
+    },
+  };
+};
+
+const digits = Buffer.from("0123456789");
+
+test("PartialFileMedia should wait until the range is available", async () => {
+  const download = newDownload(digits);
+  const media = new PartialFileMedia(new MemoryFileMedia(digits, "d"), download);
+  let settled = false;
+  const read = media.createReadStream({ start: 2, end: 5 }).then((s) => {
+    settled = true;
+    return streamToBuffer(s);
+  });
+
+  download.receive(4);
+  await new Promise((resolve) => setTimeout(resolve, 5));
+  expect(settled).toBe(false);
+
+  download.receive(4);
+  expect((await read).toString()).toBe("2345");
+  expect(download.listenerCount).toBe(0);
+});
+
+test("PartialFileMedia should handle listeners called while subscribing", async () => {
+  let checks = 0;
+  let unsubscribed = false;
+  const media = new PartialFileMedia(new MemoryFileMedia(digits, "d"), {
+    // the data arrives between the first check and the subscription
+    isAvailable: () => checks++ > 0,
+    onChange(listener) {
+      listener();
+      return () => {
+        unsubscribed = true;
+      };
+    },
+    timeout: 1000,
+  });
+  const stream = await media.createReadStream({ start: 0, end: 1 });
+  expect((await streamToBuffer(stream)).toString()).toBe("01");
+  expect(unsubscribed).toBe(true);
+});
+
+test("PartialFileMedia should time out", async () => {
+  const download = newDownload(digits);
+  const media = new PartialFileMedia(new MemoryFileMedia(digits, "d"), {
+    ...download,
+    timeout: 5,
+  });
+  await expect(media.createReadStream({ start: 0, end: 1 })).rejects.toThrow(
+    "Timed out after 5ms waiting for bytes 0-1 of d"
+  );
+  expect(download.listenerCount).toBe(0);
+});
+
+test("PartialFileMedia should reject when the source fails", async () => {
+  const download = newDownload(digits);
+  const media = new PartialFileMedia(new MemoryFileMedia(digits, "d"), download);
+  const read = media.createReadStream({ start: 0, end: 1 });
+  download.fail(new Error("peer went away"));
+  await expect(read).rejects.toThrow("peer went away");
+});
+
+test("rar package can be parsed while the volume is still downloading", async () => {
+  const archive = mockRarArchive([
+    { name: "a.txt", data: Buffer.alloc(300, "a") },
+    { name: "b.txt", data: Buffer.alloc(300, "b") },
+  ]);
+  const download = newDownload(archive);
+  const timer = setInterval(() => download.receive(64), 1);
+  const media = new PartialFileMedia(
+    new MemoryFileMedia(archive, "partial.rar"),
+    download
+  );
+  const files = await new RarFilesPackage([media]).parse();
+  const contents = await Promise.all(files.map((f) => f.readToEnd()));
+  clearInterval(timer);
+  expect(contents).toEqual([Buffer.alloc(300, "a"), Buffer.alloc(300, "b")]);
+});
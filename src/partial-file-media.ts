@@ -0,0 +1,81 @@
+import { IFileMedia, IReadInterval } from "./interfaces.js";
+
+export interface PartialFileMediaOptions {
+  isAvailable(interval: IReadInterval): boolean;
+  onChange(listener: (error?: Error) => void): () => void;
+  timeout?: number;
+}
+
+export class PartialFileMedia implements IFileMedia {
+  name: string;
+  length: number;
+  constructor(
+    private media: IFileMedia,
+    private opts: PartialFileMediaOptions
+  ) {
+    this.name = media.name;
+    this.length = media.length;
+  }
+  async createReadStream(interval: IReadInterval) {
+    await this.waitFor({
+      start: interval.start,
+      end: Math.min(interval.end, this.length - 1),
+    });
+    return this.media.createReadStream(interval);
+  }
+  private waitFor(interval: IReadInterval) {
+    const { isAvailable, onChange, timeout } = this.opts;
+    if (isAvailable(interval)) {
+      return Promise.resolve();
+    }
+    return new Promise<void>((resolve, reject) => {
+      let timer: NodeJS.Timeout | undefined;
+      let settled = false;
+      // onChange may call the listener before it returns the unsubscribe
+      let unsubscribe: (() => void) | undefined;
+      const done = (error?: Error) => {
+        if (settled) {
+          return;
+        }
+        settled = true;
+        clearTimeout(timer);
+        unsubscribe?.();
+        if (error) {
+          reject(error);
+        } else {
+          resolve();
+        }
+      };
+      unsubscribe = onChange((error) => {
+        if (error) {
+          done(
+            new Error(
+              `Failed waiting for bytes ${interval.start}-${interval.end} of ${this.name}: ${error.message}`
+            )
+          );
+        } else if (isAvailable(interval)) {
+          done();
+        }
+      });
+      if (settled) {
+        unsubscribe();
+        return;
+      }
+      if (timeout) {
+        timer = setTimeout(
+          () =>
+            done(
+              new Error(
+                `Timed out after ${timeout}ms waiting for bytes ${interval.start}-${interval.end} of ${this.name}`
+              )
+            ),
+          timeout
+        );
+      }
+      // the range may have completed before the listener was attached
+      if (isAvailable(interval)) {
+        done();
+      }
+    });
+  }
+}
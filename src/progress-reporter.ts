@@ -0,0 +1,36 @@
+import { IProgressSink } from "./interfaces.js";
+
+export const DEFAULT_PROGRESS_INTERVAL = 1024 * 1024;
+
+export class ProgressReporter {
+  private bytes = 0;
+  private reportedBytes = 0;
+  private volumeIndex = 0;
+  constructor(
+    private name: string,
+    private sink: IProgressSink,
+    private interval = DEFAULT_PROGRESS_INTERVAL
+  ) {}
+  add(length: number, volumeIndex: number) {
+    this.bytes += length;
+    this.volumeIndex = volumeIndex;
+    if (this.bytes - this.reportedBytes >= this.interval) {
+      this.report();
+    }
+  }
+  flush() {
+    if (this.bytes !== this.reportedBytes) {
+      this.report();
+    }
+  }
+  private report() {
+    this.reportedBytes = this.bytes;
+    // stored entries are copied verbatim, so every byte read is a byte written
+    this.sink.onProgress({
+      name: this.name,
+      volumeIndex: this.volumeIndex,
+      bytesRead: this.bytes,
+      bytesWritten: this.bytes,
+    });
+  }
+}
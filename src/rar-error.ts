@@ -0,0 +1,43 @@
+export type RarErrorCode =
+  | "NO_SIGNATURE"
+  | "UNSUPPORTED_VERSION"
+  | "ENCRYPTED_HEADERS"
+  | "VOLUME_ORDER"
+  | "VOLUME_MISMATCH"
+  | "MISSING_VOLUME"
+  | "INVALID_HEADER"
+  | "LIMIT_EXCEEDED"
+  | "UNSUPPORTED_METHOD"
+  | "ENCRYPTED_ENTRY"
+  | "CHECKSUM_MISMATCH"
+  | "INDEX_MISMATCH"
+  | "UNSUPPORTED_FEATURE";
+
+export interface RarErrorContext {
+  volume?: string;
+  volumeIndex?: number;
+  offset?: number;
+  entry?: string;
+  feature?: string;
+}
+
+export class RarError extends Error {
+  override name = "RarError";
+  readonly volume?: string | undefined;
+  readonly volumeIndex?: number | undefined;
+  readonly offset?: number | undefined;
+  readonly entry?: string | undefined;
+  readonly feature?: string | undefined;
+  constructor(
+    public readonly code: RarErrorCode,
+    message: string,
+    context: RarErrorContext = {}
+  ) {
+    super(message);
+    this.volume = context.volume;
+    this.volumeIndex = context.volumeIndex;
+    this.offset = context.offset;
+    this.entry = context.entry;
+    this.feature = context.feature;
+  }
+}
@@ -1,7 +1,7 @@
 import { expect, test } from "vitest";
 import { IFileMedia } from "./interfaces.js";
 import { Readable } from "stream";
-import { makeRarFileBundle } from "./rar-file-bundle.js";
+import { findVolumeNames, makeRarFileBundle } from "./rar-file-bundle.js";
 
 const newFileMedia = (name: string) =>
   ({
@@ -85,3 +85,129 @@ test("RarFileBundle should filter out non rar files", () => {
   const unFilteredInstance = makeRarFileBundle(fileMedias);
   expect(unFilteredInstance.fileNames).toEqual(filteredFileNames);
 });
+
+test("RarFileBundle should sort part file names by number regardless of padding", () => {
+  const unsortedFileNames = ["a.part10.rar", "a.part2.rar", "a.part1.rar"];
+  const fileMedias = unsortedFileNames.map(newFileMedia);
+  const instance = makeRarFileBundle(fileMedias);
+  expect(instance.fileNames).toEqual(["a.part1.rar", "a.part2.rar", "a.part10.rar"]);
+});
+
+test("findVolumeNames should find part volumes belonging to the same set", () => {
+  const names = [
+    "a (1).part1.rar",
+    "a (1).part2.rar",
+    "a (1).nfo",
+    "a.part1.rar",
+    "a (1).part10.rar",
+  ];
+  expect(findVolumeNames("a (1).part1.rar", names)).toEqual([
+    "a (1).part1.rar",
+    "a (1).part2.rar",
+    "a (1).part10.rar",
+  ]);
+});
+
+test("findVolumeNames should find rxx volumes belonging to the same set", () => {
+  const names = ["b.rar", "b.r00", "b.R01", "b.r00.bak", "bb.r00", "b.sfv"];
+  expect(findVolumeNames("b.rar", names)).toEqual(["b.rar", "b.r00", "b.R01"]);
+  expect(findVolumeNames("b.r01", names)).toEqual(["b.rar", "b.r00", "b.R01"]);
+});
+
+test("RarFileBundle should sort rxx file names past r99 into the s00 series", () => {
+  const unsortedFileNames = ["a.s01", "a.r99", "a.s00", "a.rar", "a.r00"];
+  const fileMedias = unsortedFileNames.map(newFileMedia);
+  const instance = makeRarFileBundle(fileMedias);
+  expect(instance.fileNames).toEqual(["a.rar", "a.r00", "a.r99", "a.s00", "a.s01"]);
+});
+
+test("self-extracting first volumes are ordered first", () => {
+  const bundle = makeRarFileBundle(
+    ["setup.r00", "setup.exe", "setup.r01"].map(newFileMedia)
+  );
+  expect(bundle.fileNames).toEqual(["setup.exe", "setup.r00", "setup.r01"]);
+  expect(
+    findVolumeNames("setup.part1.exe", [
+      "setup.part1.exe",
+      "setup.part2.rar",
+      "other.exe",
+    ])
+  ).toEqual(["setup.part1.exe", "setup.part2.rar"]);
+});
+
+test("RarFileBundle only takes .exe files with volumes of their set", () => {
+  const bundle = makeRarFileBundle(
+    ["a.rar", "a.r00", "setup.exe", "other.part1.exe"].map(newFileMedia)
+  );
+  expect(bundle.fileNames).toEqual(["a.rar", "a.r00"]);
+  expect(bundle.sfxCandidates.map((file) => file.name)).toEqual([
+    "setup.exe",
+    "other.part1.exe",
+  ]);
+});
+
+test("RarFileBundle takes in self-extracting candidates once accepted", () => {
+  const bundle = makeRarFileBundle([newFileMedia("setup.exe")]);
+  expect(bundle.length).toBe(0);
+  bundle.acceptSfxCandidate(bundle.sfxCandidates[0]!);
+  expect(bundle.fileNames).toEqual(["setup.exe"]);
+  expect(bundle.sfxCandidates).toEqual([]);
+});
+
+const sizedFileMedia = (name: string, length: number) => ({
+  ...newFileMedia(name),
+  length,
+});
+
+test("RarFileBundle should drop duplicate volumes", () => {
+  const bundle = makeRarFileBundle([
+    sizedFileMedia("a.rar", 10),
+    sizedFileMedia("a.r00", 10),
+    sizedFileMedia("a.rar", 10),
+    sizedFileMedia("A.R00", 10),
+    sizedFileMedia("a.r01", 5),
+  ]);
+  expect(bundle.fileNames).toEqual(["a.rar", "a.r00", "a.r01"]);
+  expect(bundle.duplicates.map((f) => f.name)).toEqual(["a.rar", "A.R00"]);
+  expect(bundle.shorterCopies).toEqual([]);
+});
+
+test("RarFileBundle should keep volumes of different sets apart", () => {
+  const names = (input: string[]) =>
+    makeRarFileBundle(input.map((name) => sizedFileMedia(name, 10))).fileNames;
+  expect(names(["a.rar", "b.rar"])).toHaveLength(2);
+  expect(names(["a.rar", "copy/a.rar"])).toHaveLength(2);
+  expect(names(["dir1/subs.part1.rar", "dir2/subs.part1.rar"])).toHaveLength(2);
+});
+
+test("RarFileBundle should keep the longest copy of a part volume", () => {
+  const truncated = sizedFileMedia("a.part2.rar", 5);
+  const complete = sizedFileMedia("a.part02.rar", 10);
+  const bundle = makeRarFileBundle([
+    sizedFileMedia("a.part1.rar", 10),
+    truncated,
+    complete,
+  ]);
+  expect(bundle.fileNames).toEqual(["a.part1.rar", "a.part02.rar"]);
+  expect(bundle.duplicates).toEqual([]);
+  expect(bundle.shorterCopies).toEqual([truncated]);
+});
+
+test("RarFileBundle should name the volumes missing from a set", () => {
+  const numeric = makeRarFileBundle(
+    ["a.rar", "a.r00", "a.r03", "a.r99", "a.s01"].map(newFileMedia)
+  );
+  expect(numeric.missingBefore(1)).toEqual([]);
+  expect(numeric.missingBefore(2)).toEqual(["a.r01", "a.r02"]);
+  expect(numeric.missingBefore(4)).toEqual(["a.s00"]);
+  expect(numeric.nextVolumeName).toBe("a.s02");
+  expect(
+    makeRarFileBundle(["a.rar", "a.z99"].map(newFileMedia)).nextVolumeName
+  ).toBe(undefined);
+
+  const parts = makeRarFileBundle(
+    ["a.part01.rar", "a.part04.rar"].map(newFileMedia)
+  );
+  expect(parts.missingBefore(1)).toEqual(["a.part02.rar", "a.part03.rar"]);
+  expect(parts.nextVolumeName).toBe("a.part05.rar");
+});
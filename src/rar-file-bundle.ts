@@ -1,6 +1,9 @@
-const RXX_EXTENSION = /\.R(\d\d)$|.RAR$/i;
-const RAR_EXTENSION = /.RAR$/i;
-const PARTXX_RAR_EXTENSION = /.PART(\d\d).RAR/i;
+const RXX_EXTENSION = /\.([R-Z])(\d\d)$|\.(RAR|EXE)$/i;
+const RAR_EXTENSION = /\.(RAR|EXE)$/i;
+const PARTXX_RAR_EXTENSION = /.PART(\d+).(RAR|EXE)/i;
+const EXE_EXTENSION = /\.EXE$/i;
+// .z99, after the .r, .s, ... .z series of 100 volumes each
+const LAST_RXX_VOLUME_NUMBER = 899;
 import { IFileMedia } from "./interfaces.js";
 
 const isPartXXExtension = (fileMedias: IFileMedia[] = []) => {
@@ -15,13 +18,143 @@ const isPartXXExtension = (fileMedias: IFileMedia[] = []) => {
   }
 };
 
+// After .r99 the old naming scheme continues with .s00, .t00 and so on.
+const rxxVolumeNumber = (name: string) => {
+  const match = name.match(RXX_EXTENSION);
+  if (!match || !match[1]) {
+    return 0;
+  }
+  const series = match[1].toUpperCase().charCodeAt(0) - "R".charCodeAt(0);
+  return series * 100 + +match[2]!;
+};
+
+// The name shared by the volumes of a set, e.g. "dir/a" for "dir/a.r00" and
+// "dir/a.part01.rar". Volumes of different directories belong to different
+// sets.
+export const volumeSetName = (name: string) =>
+  (PARTXX_RAR_EXTENSION.test(name)
+    ? name.replace(/\.PART\d+\.(RAR|EXE)$/i, "")
+    : name.replace(/\.(RAR|EXE|[R-Z]\d\d)$/i, "")
+  ).toLowerCase();
+
+// Keeps one volume per set and volume number. Copies of the same length are
+// duplicates. Of copies that differ the longest is kept, as the others are
+// likely incomplete downloads, and the shorter ones are reported apart.
+const removeDuplicates = (
+  fileMedias: IFileMedia[],
+  volumeNumber: (name: string) => number
+) => {
+  const kept = new Map<string, IFileMedia>();
+  const duplicates: IFileMedia[] = [];
+  const shorterCopies: IFileMedia[] = [];
+  for (const file of fileMedias) {
+    const key = `${volumeSetName(file.name)}:${volumeNumber(file.name)}`;
+    const existing = kept.get(key);
+    if (!existing) {
+      kept.set(key, file);
+      continue;
+    }
+    const [longer, shorter] =
+      file.length > existing.length ? [file, existing] : [existing, file];
+    kept.set(key, longer);
+    (shorter.length === longer.length ? duplicates : shorterCopies).push(
+      shorter
+    );
+  }
+  return { files: [...kept.values()], duplicates, shorterCopies };
+};
+
+// Any program ends in .exe, so one is only taken as the first volume when
+// volumes of the same set are given with it. The others are candidates for
+// a self-extracting archive until a RAR signature is found in them.
+const splitSfxCandidates = (fileMedias: IFileMedia[]) => {
+  const names = fileMedias.map((file) => file.name);
+  const volumes: IFileMedia[] = [];
+  const sfxCandidates: IFileMedia[] = [];
+  for (const file of fileMedias) {
+    const isCandidate =
+      EXE_EXTENSION.test(file.name) &&
+      findVolumeNames(file.name, names).every((name) => EXE_EXTENSION.test(name));
+    (isCandidate ? sfxCandidates : volumes).push(file);
+  }
+  return { volumes, sfxCandidates };
+};
+
+const partVolumeNumber = (name: string) =>
+  +(name.match(PARTXX_RAR_EXTENSION)?.[1] ?? 0);
+
+const numericVolumeNumber = (name: string) =>
+  name.match(RAR_EXTENSION) ? -1 : rxxVolumeNumber(name);
+
+// Names the volume with the given number after an existing one of the set.
+const rxxVolumeName = (template: string, number: number) => {
+  if (number < 0) {
+    return template.replace(/\.[R-Z]\d\d$/i, ".rar");
+  }
+  const extension = `${String.fromCharCode(
+    "r".charCodeAt(0) + Math.floor(number / 100)
+  )}${String(number % 100).padStart(2, "0")}`;
+  return template.replace(/\.(RAR|EXE|[R-Z]\d\d)$/i, `.${extension}`);
+};
+
+const partVolumeName = (template: string, number: number) =>
+  template.replace(
+    /(\.PART)(\d+)(\.(RAR|EXE))$/i,
+    (_, part: string, digits: string, extension: string) =>
+      `${part}${String(number).padStart(digits.length, "0")}${extension}`
+  );
+
+// Names of the volumes missing between the given file and the one before it.
+const missingBefore = (
+  fileMedias: IFileMedia[],
+  index: number,
+  volumeNumber: (name: string) => number,
+  volumeName: (template: string, number: number) => string
+) => {
+  const file = fileMedias[index];
+  const previous = fileMedias[index - 1];
+  if (!file || !previous) {
+    return [];
+  }
+  const missing: string[] = [];
+  const last = volumeNumber(file.name);
+  for (let number = volumeNumber(previous.name) + 1; number < last; ++number) {
+    missing.push(volumeName(file.name, number));
+  }
+  return missing;
+};
+
 class NumericRarFileBundle {
-  constructor(private fileMedias: IFileMedia[] = []) {
+  duplicates: IFileMedia[] = [];
+  // copies of a volume that are shorter than the one used
+  shorterCopies: IFileMedia[] = [];
+  constructor(
+    private fileMedias: IFileMedia[] = [],
+    public sfxCandidates: IFileMedia[] = []
+  ) {
     if (this.fileMedias.length > 0) {
       this.filter();
       this.sort();
+      this.removeDuplicates();
     }
   }
+  // Takes a candidate in once it is known to be a volume.
+  acceptSfxCandidate(file: IFileMedia) {
+    this.sfxCandidates = this.sfxCandidates.filter((other) => other !== file);
+    this.fileMedias.push(file);
+    this.filter();
+    this.sort();
+    this.removeDuplicates();
+  }
+  removeDuplicates() {
+    const { files, duplicates, shorterCopies } = removeDuplicates(
+      this.fileMedias,
+      numericVolumeNumber
+    );
+    this.fileMedias = files;
+    this.duplicates.push(...duplicates);
+    this.shorterCopies.push(...shorterCopies);
+  }
   filter() {
     this.fileMedias = this.fileMedias.filter(
       (file) => file.name && file.name.match(RXX_EXTENSION)
@@ -34,14 +167,30 @@ class NumericRarFileBundle {
       } else if (second.name.match(RAR_EXTENSION)) {
         return 1;
       } else {
-        const firstMatch = first.name.match(RXX_EXTENSION);
-        const secondMatch = second.name.match(RXX_EXTENSION);
-        const firstNumber = +((firstMatch && firstMatch[1]) || 0);
-        const secondNumber = +((secondMatch && secondMatch[1]) || 0);
-        return firstNumber - secondNumber;
+        return rxxVolumeNumber(first.name) - rxxVolumeNumber(second.name);
       }
     });
   }
+  missingBefore(index: number) {
+    return missingBefore(
+      this.fileMedias,
+      index,
+      numericVolumeNumber,
+      rxxVolumeName
+    );
+  }
+  // The volume that would follow the last one given, if the naming scheme
+  // has one. The old scheme ends at .z99.
+  get nextVolumeName(): string | undefined {
+    const last = this.fileMedias[this.fileMedias.length - 1];
+    if (!last) {
+      return undefined;
+    }
+    const number = numericVolumeNumber(last.name) + 1;
+    return number <= LAST_RXX_VOLUME_NUMBER
+      ? rxxVolumeName(last.name, number)
+      : undefined;
+  }
 
   get length() {
     return this.fileMedias.length;
@@ -55,25 +204,59 @@ class NumericRarFileBundle {
 }
 
 class PartXXRarBundle {
-  constructor(private fileMedias: IFileMedia[] = []) {
+  duplicates: IFileMedia[] = [];
+  // copies of a volume that are shorter than the one used
+  shorterCopies: IFileMedia[] = [];
+  constructor(
+    private fileMedias: IFileMedia[] = [],
+    public sfxCandidates: IFileMedia[] = []
+  ) {
     if (this.fileMedias.length > 0) {
       this.filter();
       this.sort();
+      this.removeDuplicates();
     }
   }
+  // Takes a candidate in once it is known to be a volume.
+  acceptSfxCandidate(file: IFileMedia) {
+    this.sfxCandidates = this.sfxCandidates.filter((other) => other !== file);
+    this.fileMedias.push(file);
+    this.filter();
+    this.sort();
+    this.removeDuplicates();
+  }
+  removeDuplicates() {
+    const { files, duplicates, shorterCopies } = removeDuplicates(
+      this.fileMedias,
+      partVolumeNumber
+    );
+    this.fileMedias = files;
+    this.duplicates.push(...duplicates);
+    this.shorterCopies.push(...shorterCopies);
+  }
   filter() {
     this.fileMedias = this.fileMedias.filter((file) =>
       file.name.match(PARTXX_RAR_EXTENSION)
     );
   }
   sort() {
-    this.fileMedias.sort((first, second) => {
-      const firstMatch = first.name.match(PARTXX_RAR_EXTENSION);
-      const secondMatch = second.name.match(PARTXX_RAR_EXTENSION);
-      const firstNumber = +((firstMatch && firstMatch[1]) || 0);
-      const secondNumber = +((secondMatch && secondMatch[1]) || 0);
-      return firstNumber - secondNumber;
-    });
+    this.fileMedias.sort(
+      (first, second) =>
+        partVolumeNumber(first.name) - partVolumeNumber(second.name)
+    );
+  }
+  missingBefore(index: number) {
+    return missingBefore(
+      this.fileMedias,
+      index,
+      partVolumeNumber,
+      partVolumeName
+    );
+  }
+  // The volume that would follow the last one given.
+  get nextVolumeName(): string | undefined {
+    const last = this.fileMedias[this.fileMedias.length - 1];
+    return last && partVolumeName(last.name, partVolumeNumber(last.name) + 1);
   }
 
   get length() {
@@ -87,11 +270,29 @@ class PartXXRarBundle {
   }
 }
 
+const escapeRegExp = (str: string) =>
+  str.replace(/[.*+?^${}()|[\]\\]/g, "\\$&");
+
+export const findVolumeNames = (volumeName: string, names: string[]) => {
+  const partMatch = volumeName.match(/^(.*)\.PART\d+\.(RAR|EXE)$/i);
+  const volumePattern = partMatch
+    ? new RegExp(
+        `^${escapeRegExp(partMatch[1]!)}\\.PART\\d+\\.(RAR|EXE)$`,
+        "i"
+      )
+    : new RegExp(
+        `^${escapeRegExp(volumeName.replace(/\.(RAR|EXE|[R-Z]\d\d)$/i, ""))}\\.(RAR|EXE|[R-Z]\\d\\d)$`,
+        "i"
+      );
+  return names.filter((name) => volumePattern.test(name));
+};
+
 export type RarFileBundle = PartXXRarBundle | NumericRarFileBundle;
 export const makeRarFileBundle = (
   fileMedias: IFileMedia[] = []
 ): RarFileBundle => {
-  return isPartXXExtension(fileMedias)
-    ? new PartXXRarBundle(fileMedias)
-    : new NumericRarFileBundle(fileMedias);
+  const { volumes, sfxCandidates } = splitSfxCandidates(fileMedias);
+  return isPartXXExtension(volumes.length ? volumes : sfxCandidates)
+    ? new PartXXRarBundle(volumes, sfxCandidates)
+    : new NumericRarFileBundle(volumes, sfxCandidates);
 };
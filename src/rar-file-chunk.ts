@@ -2,22 +2,25 @@ import { IFileMedia } from "./interfaces.js";
 
 export class RarFileChunk {
   constructor(
-    private fileMedia: IFileMedia,
+    readonly fileMedia: IFileMedia,
     public startOffset: number,
-    public endOffset: number
+    public endOffset: number,
+    public volumeIndex = 0
   ) {}
   padEnd(endPadding: number) {
     return new RarFileChunk(
       this.fileMedia,
       this.startOffset,
-      this.endOffset - endPadding
+      this.endOffset - endPadding,
+      this.volumeIndex
     );
   }
   padStart(startPadding: number) {
     return new RarFileChunk(
       this.fileMedia,
       this.startOffset + startPadding,
-      this.endOffset
+      this.endOffset,
+      this.volumeIndex
     );
   }
   get length() {
@@ -0,0 +1,73 @@
+import { afterAll, beforeAll, bench, describe } from "vitest";
+import fs from "fs";
+import os from "os";
+import path from "path";
+import { finished } from "stream/promises";
+import { CachedFileMedia } from "./cached-file-media.js";
+import { IFileMedia } from "./interfaces.js";
+import { LocalFileMedia } from "./local-file-media.js";
+import { MemoryFileMedia } from "./memory-file-media.js";
+import { mockRarArchive } from "./parsing/__mocks__/mock-rar-archive.js";
+import { RarFilesPackage } from "./rar-files-package.js";
+import { crc32 } from "./utils.js";
+
+// Fixtures are generated with the test mocks, so nothing large is checked in.
+const MANY_ENTRIES = 10_000;
+const LARGE_SIZE = 64 * 1024 * 1024;
+
+const manyEntries = new MemoryFileMedia(
+  mockRarArchive(
+    Array.from({ length: MANY_ENTRIES }, (_, i) => ({
+      name: `dir-${i % 100}/file-${i}.txt`,
+      data: Buffer.from(String(i)),
+    }))
+  ),
+  "many.rar"
+);
+
+const largeData = Buffer.alloc(LARGE_SIZE, 0x5a);
+let largeDir: string;
+let largePath: string;
+
+beforeAll(() => {
+  largeDir = fs.mkdtempSync(path.join(os.tmpdir(), "rar-stream-bench-"));
+  largePath = path.join(largeDir, "large.rar");
+  fs.writeFileSync(
+    largePath,
+    mockRarArchive([{ name: "large.bin", data: largeData }])
+  );
+});
+
+afterAll(() => {
+  fs.rmSync(largeDir, { recursive: true });
+});
+
+const readAll = async (media: IFileMedia) => {
+  const [file] = await new RarFilesPackage([media]).parse();
+  await finished(
+    (await file!.createReadStream({ start: 0, end: file!.length - 1 })).resume()
+  );
+};
+
+describe("parse", () => {
+  bench(`${MANY_ENTRIES} entries`, async () => {
+    await new RarFilesPackage([manyEntries]).parse();
+  });
+});
+
+describe("read a 64 MiB stored entry", () => {
+  bench("LocalFileMedia", () => readAll(new LocalFileMedia(largePath)), {
+    iterations: 10,
+  });
+  bench(
+    "CachedFileMedia",
+    () => readAll(new CachedFileMedia(new LocalFileMedia(largePath))),
+    { iterations: 10 }
+  );
+});
+
+describe("crc32", () => {
+  bench("64 MiB", () => {
+    crc32(largeData);
+  });
+});
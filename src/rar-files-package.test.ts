@@ -2,12 +2,17 @@
 import { expect, test } from "vitest";
 import path from "path";
 import fs from "fs";
+import { Readable } from "stream";
 
 import { RarFilesPackage } from "./rar-files-package.js";
 import { streamToBuffer } from "./stream-utils.js";
 import { makeRarFileBundle } from "./rar-file-bundle.js";
 import { InnerFile } from "./inner-file.js";
+import { FindOpts, IFileMedia, IProgress } from "./interfaces.js";
+import { RarError } from "./rar-error.js";
 import { LocalFileMedia } from "./local-file-media.js";
+import { MemoryFileMedia } from "./memory-file-media.js";
+import { mockRarArchive } from "./parsing/__mocks__/mock-rar-archive.js";
 
 const fixturePath = path.resolve(__dirname, "./__fixtures__");
 
@@ -218,6 +223,33 @@ test("multi rar file with many inner files can be read as whole", async () => {
   expect(rarFile4!.length).toBe(splitted4.length);
 });
 
+test("inner file length matches the original file across volumes", async () => {
+  const rarPackage = new RarFilesPackage(multipleRarFileWithManyInnerFiles);
+  const files = await rarPackage.parse();
+  const originals = [
+    multiSplitted1FilePath,
+    multiSplitted2FilePath,
+    multiSplitted3FilePath,
+    multiSplitted4FilePath,
+  ].map((p) => fs.readFileSync(p));
+
+  expect(files.map((f) => f.length)).toEqual(originals.map((b) => b.length));
+
+  const last = files[3]!;
+  const tail = await streamToBuffer(
+    await last.createReadStream({ start: last.length - 10, end: last.length - 1 })
+  );
+  expect(tail).toEqual(originals[3]!.subarray(-10));
+
+  // ranges that span the chunks of several volumes
+  for (const [i, file] of files.entries()) {
+    const range = await streamToBuffer(
+      await file.createReadStream({ start: 1, end: file.length - 2 })
+    );
+    expect(range).toEqual(originals[i]!.subarray(1, -1));
+  }
+});
+
 test("multi rar file with many inner files can be read in parts", async () => {
   const interval = { start: 56, end: 200 };
   const rarPackage = new RarFilesPackage(multipleRarFileWithManyInnerFiles);
@@ -260,3 +292,1238 @@ test("multi rar file with many inner files can be read in parts", async () => {
   expect(rarFile3Buffer).toEqual(splittedFile3Buffer);
   expect(rarFile4Buffer).toEqual(splittedFile4Buffer);
 });
+
+test("rar package filter is called with a file index across all volumes", async () => {
+  const rarPackage = new RarFilesPackage(multipleRarFileWithManyInnerFiles);
+  const calls: [string, number][] = [];
+  await rarPackage.parse({
+    filter: (name, idx) => {
+      calls.push([name, idx]);
+      return false;
+    },
+  });
+  expect(calls).toEqual([
+    ["splitted1.txt", 0],
+    ["splitted2.txt", 1],
+    ["splitted3.txt", 2],
+    ["splitted4.txt", 3],
+  ]);
+});
+
+test("rar package filter can select a file from a later volume", async () => {
+  const rarPackage = new RarFilesPackage(multipleRarFileWithManyInnerFiles);
+  const files = await rarPackage.parse({
+    filter: (name) => name === "splitted3.txt",
+  });
+  const [content] = await readToEnd(files);
+
+  expect(files.map((f) => f.name)).toEqual(["splitted3.txt"]);
+  expect(content).toEqual(fs.readFileSync(multiSplitted3FilePath));
+});
+
+test("rar package filter keeps all parts of a file spanning volumes", async () => {
+  const rarPackage = new RarFilesPackage(multipleRarFileWithManyInnerFiles);
+  const files = await rarPackage.parse({
+    filter: (_, idx) => idx === 3,
+  });
+  const [content] = await readToEnd(files);
+
+  expect(files.map((f) => f.name)).toEqual(["splitted4.txt"]);
+  expect(content).toEqual(fs.readFileSync(multiSplitted4FilePath));
+});
+
+test("rar package maxFiles limits results without a filter", async () => {
+  const rarPackage = new RarFilesPackage(multipleRarFileWithManyInnerFiles);
+  const parsedVolumes: string[] = [];
+  rarPackage.on("file-parsed", (file) => parsedVolumes.push(file.name));
+  const files = await rarPackage.parse({ maxFiles: 1 });
+
+  expect(files.map((f) => f.name)).toEqual(["splitted1.txt"]);
+  expect(parsedVolumes).toEqual(["multi-splitted.rar"]);
+});
+
+test("rar package maxFiles stops once enough filtered files are found", async () => {
+  const rarPackage = new RarFilesPackage(multipleRarFileWithManyInnerFiles);
+  const files = await rarPackage.parse({
+    filter: (name) => name !== "splitted1.txt",
+    maxFiles: 2,
+  });
+
+  expect(files.map((f) => f.name)).toEqual(["splitted2.txt", "splitted3.txt"]);
+});
+
+test("rar package can be created from the first volume of a set", async () => {
+  const rarPackage = RarFilesPackage.fromFirstVolume(
+    path.resolve(fixturePath, "multi/multi.rar")
+  );
+  expect(rarPackage.rarFileBundle.fileNames).toEqual([
+    "multi.rar",
+    "multi.r00",
+    "multi.r01",
+  ]);
+
+  const [rarFileBuffer] = await rarPackage.parse().then(readToEnd);
+  expect(rarFileBuffer).toEqual(fs.readFileSync(multiFilePath));
+});
+
+test("rar package from first volume ignores files outside the volume set", () => {
+  const rarPackage = RarFilesPackage.fromFirstVolume(
+    path.resolve(fixturePath, "multi-splitted/multi-splitted.rar")
+  );
+  expect(rarPackage.rarFileBundle.fileNames).toEqual([
+    "multi-splitted.rar",
+    "multi-splitted.r00",
+    "multi-splitted.r01",
+  ]);
+});
+
+test("rar package rejects volumes whose names do not match their volume number", async () => {
+  const volume = (content: string, name: string) =>
+    new MemoryFileMedia(
+      fs.readFileSync(path.resolve(fixturePath, "multi", content)),
+      name
+    );
+  const rarPackage = new RarFilesPackage([
+    volume("multi.rar", "multi.rar"),
+    volume("multi.r01", "multi.r00"),
+    volume("multi.r00", "multi.r01"),
+  ]);
+  await expect(rarPackage.parse()).rejects.toThrow(
+    "Volume multi.r01 is out of order: expected volume 2, found volume 1"
+  );
+});
+
+test("rar package rejects a first volume that is not sorted first", async () => {
+  const volume = (content: string, name: string) =>
+    new MemoryFileMedia(
+      fs.readFileSync(path.resolve(fixturePath, "multi-splitted", content)),
+      name
+    );
+  const rarPackage = new RarFilesPackage([
+    volume("multi-splitted.r00", "multi-splitted.rar"),
+    volume("multi-splitted.rar", "multi-splitted.r00"),
+  ]);
+  await expect(rarPackage.parse()).rejects.toThrow("out of order");
+});
+
+test("rar package exposes archive info after parsing", async () => {
+  const rarPackage = new RarFilesPackage(multipleRarFileWithOneInnerFile);
+  expect(rarPackage.archiveInfo).toBe(undefined);
+  await rarPackage.parse();
+  expect(rarPackage.archiveInfo).toEqual({
+    isMultiVolume: true,
+    isSolid: false,
+    isLocked: false,
+    hasRecovery: false,
+    hasComment: false,
+    hasEncryptedHeaders: false,
+    isNewNameScheme: false,
+    volumeCount: 3,
+    sfxStubSize: 0,
+  });
+});
+
+test("rar package archive info of a single volume archive", async () => {
+  const rarPackage = new RarFilesPackage(singleFileRarWithOneInnerFile);
+  await rarPackage.parse();
+  expect(rarPackage.archiveInfo?.isMultiVolume).toBe(false);
+  expect(rarPackage.archiveInfo?.volumeCount).toBe(1);
+});
+
+const corruptedSingleRar = () => {
+  const buffer = fs.readFileSync(path.resolve(fixturePath, "single/single.rar"));
+  buffer[100] ^= 0xff;
+  return new MemoryFileMedia(buffer, "single.rar");
+};
+
+test("inner files verify against the crc of their last volume", async () => {
+  for (const medias of [
+    singleRarWithManyInnerFiles,
+    multipleRarFileWithOneInnerFile,
+    multipleRarFileWithManyInnerFiles,
+  ]) {
+    const files = await new RarFilesPackage(medias).parse();
+    await Promise.all(files.map((file) => file.verify()));
+  }
+});
+
+test("inner file verify rejects corrupted data", async () => {
+  const [file] = await new RarFilesPackage([corruptedSingleRar()]).parse();
+  await expect(file!.verify()).rejects.toThrow(
+    "Checksum mismatch for single.txt: expected 0c261428"
+  );
+});
+
+test("rar package test reports every entry", async () => {
+  const results = await new RarFilesPackage(
+    multipleRarFileWithManyInnerFiles
+  ).test();
+  expect(results).toEqual([
+    { name: "splitted1.txt", ok: true },
+    { name: "splitted2.txt", ok: true },
+    { name: "splitted3.txt", ok: true },
+    { name: "splitted4.txt", ok: true },
+  ]);
+
+  const [corrupted] = await new RarFilesPackage([corruptedSingleRar()]).test();
+  expect(corrupted!.ok).toBe(false);
+  expect(corrupted!.error).toBeInstanceOf(RarError);
+  expect((corrupted!.error as RarError).code).toBe("CHECKSUM_MISMATCH");
+});
+
+test("rar package test reports entries it cannot read", async () => {
+  const results = await new RarFilesPackage([
+    new MemoryFileMedia(
+      mockRarArchive([
+        { name: "a.txt", data: Buffer.from("a") },
+        { name: "packed.txt", data: Buffer.from("b"), method: 0x33 },
+        { name: "secret.txt", data: Buffer.from("c"), flags: 0x04 },
+        { name: "d.txt", data: Buffer.from("d") },
+      ]),
+      "mixed.rar"
+    ),
+  ]).test();
+  expect(
+    results.map(({ name, ok, error }) => [name, ok, error?.message])
+  ).toEqual([
+    ["a.txt", true, undefined],
+    ["packed.txt", false, "Decompression is not implemented"],
+    ["secret.txt", false, "Decryption is not implemented"],
+    ["d.txt", true, undefined],
+  ]);
+});
+
+test("rar package test checks that volumes belong together", async () => {
+  await expect(
+    new RarFilesPackage(
+      volumeSet(
+        "multi/multi.rar",
+        "multi-splitted/multi-splitted.r00",
+        "multi/multi.r01"
+      )
+    ).test()
+  ).rejects.toThrow("Volume set.r00 does not belong to this archive");
+});
+
+test("inner file readToEnd checks the crc with the verify option", async () => {
+  const [unverified] = await new RarFilesPackage([corruptedSingleRar()]).parse();
+  const [verified] = await new RarFilesPackage([corruptedSingleRar()]).parse({
+    verify: true,
+  });
+
+  expect((await unverified!.readToEnd()).length).toBe(1890);
+  await expect(verified!.readToEnd()).rejects.toThrow("Checksum mismatch");
+});
+
+test("rar package rejects archives with encrypted headers", async () => {
+  const rarPackage = new RarFilesPackage([
+    new MemoryFileMedia(mockRarArchive([], 0x0080), "encrypted.rar"),
+  ]);
+  await expect(rarPackage.parse()).rejects.toThrow(
+    "Archive encrypted.rar has encrypted headers, which is not supported"
+  );
+});
+
+test("rar package rejects encrypted files", async () => {
+  const rarPackage = new RarFilesPackage([
+    new MemoryFileMedia(
+      mockRarArchive([{ name: "secret.txt", data: Buffer.from("x"), flags: 0x04 }]),
+      "encrypted.rar"
+    ),
+  ]);
+  await expect(rarPackage.parse()).rejects.toThrow("Decryption is not implemented");
+});
+
+test("rar package reports parse progress per volume", async () => {
+  const progress: IProgress[] = [];
+  const rarPackage = new RarFilesPackage(multipleRarFileWithOneInnerFile);
+  await rarPackage.parse({ progress: { onProgress: (p) => progress.push(p) } });
+
+  const lengths = rarPackage.rarFileBundle.files.map((f) => f.length);
+  expect(progress.map((p) => [p.name, p.volumeIndex, p.bytesRead])).toEqual([
+    ["multi.rar", 0, lengths[0]],
+    ["multi.r00", 1, lengths[0]! + lengths[1]!],
+    ["multi.r01", 2, lengths[0]! + lengths[1]! + lengths[2]!],
+  ]);
+});
+
+test("inner file streams report read progress at the configured interval", async () => {
+  const progress: IProgress[] = [];
+  const rarPackage = new RarFilesPackage(multipleRarFileWithOneInnerFile);
+  const [file] = await rarPackage.parse({
+    progress: { onProgress: (p) => progress.push(p) },
+    progressInterval: 500,
+  });
+  progress.length = 0;
+  await file!.readToEnd();
+
+  expect(progress.length).toBeGreaterThan(2);
+  for (let i = 0; i < progress.length - 1; i++) {
+    expect(progress[i + 1]!.bytesRead - progress[i]!.bytesRead).toBeGreaterThan(0);
+  }
+  const last = progress[progress.length - 1]!;
+  expect(last).toEqual({
+    name: "multi.txt",
+    volumeIndex: 2,
+    bytesRead: file!.length,
+    bytesWritten: file!.length,
+  });
+});
+
+test("inner files can be read as web streams", async () => {
+  const rarPackage = new RarFilesPackage(multipleRarFileWithOneInnerFile);
+  const [file] = await rarPackage.parse();
+  const multiFile = fs.readFileSync(multiFilePath);
+
+  const read = async (stream: Awaited<ReturnType<InnerFile["stream"]>>) => {
+    const chunks: Uint8Array[] = [];
+    for await (const chunk of stream) {
+      chunks.push(chunk);
+    }
+    return Buffer.concat(chunks);
+  };
+  expect(await read(await file!.stream())).toEqual(multiFile);
+  expect(await read(await file!.stream({ start: 10, end: 1500 }))).toEqual(
+    multiFile.subarray(10, 1501)
+  );
+});
+
+const limitedPackage = () =>
+  new RarFilesPackage([
+    new MemoryFileMedia(
+      mockRarArchive([
+        { name: "a.txt", data: Buffer.alloc(10) },
+        { name: "a-much-longer-name.txt", data: Buffer.alloc(100) },
+      ]),
+      "limits.rar"
+    ),
+  ]);
+
+test("rar package enforces parse limits", async () => {
+  await expect(
+    limitedPackage().parse({ limits: { maxFileCount: 1 } })
+  ).rejects.toThrow("Limit exceeded in limits.rar: file count 2 is above 1");
+  await expect(
+    limitedPackage().parse({ limits: { maxNameLength: 10 } })
+  ).rejects.toThrow("name length 22 is above 10");
+  await expect(
+    limitedPackage().parse({ limits: { maxHeaderSize: 40 } })
+  ).rejects.toThrow("header size 54 is above 40");
+  await expect(
+    limitedPackage().parse({ limits: { maxUnpackedSize: 50 } })
+  ).rejects.toThrow("unpacked size 100 is above 50");
+
+  const files = await limitedPackage().parse({
+    limits: {
+      maxFileCount: 2,
+      maxNameLength: 22,
+      maxHeaderSize: 54,
+      maxUnpackedSize: 100,
+    },
+  });
+  expect(files.length).toBe(2);
+});
+
+test("rar package rejects file headers shorter than their fixed fields", async () => {
+  const archive = mockRarArchive([{ name: "a.txt", data: Buffer.alloc(10) }]);
+  archive.writeUInt16LE(20, 25);
+  const rarPackage = new RarFilesPackage([
+    new MemoryFileMedia(archive, "corrupt.rar"),
+  ]);
+  await expect(rarPackage.parse()).rejects.toThrow(
+    "Invalid file header in corrupt.rar at offset 20: header size 20"
+  );
+  archive.writeUInt16LE(5, 25);
+  await expect(rarPackage.parse()).rejects.toThrow(
+    "Invalid header in corrupt.rar at offset 20: header size 5"
+  );
+});
+
+const damagedArchive = () => {
+  const archive = mockRarArchive([
+    { name: "a.txt", data: Buffer.from("first") },
+    { name: "b.txt", data: Buffer.from("second") },
+    { name: "c.txt", data: Buffer.from("third") },
+  ]);
+  // the second header starts after the marker, main header and first entry
+  const secondHeader = 7 + 13 + 32 + 5 + 5;
+  archive.fill(0xee, secondHeader + 2, secondHeader + 8);
+  return { archive, secondHeader };
+};
+
+test("rar package stops at a damaged header by default", async () => {
+  const { archive } = damagedArchive();
+  const files = await new RarFilesPackage([
+    new MemoryFileMedia(archive, "damaged.rar"),
+  ]).parse();
+  expect(files.map((f) => f.name)).toEqual(["a.txt"]);
+});
+
+test("tolerant parsing skips damaged headers and reports them", async () => {
+  const { archive, secondHeader } = damagedArchive();
+  const rarPackage = new RarFilesPackage([
+    new MemoryFileMedia(archive, "damaged.rar"),
+  ]);
+  const files = await rarPackage.parse({ tolerant: true });
+
+  expect(files.map((f) => f.name)).toEqual(["a.txt", "c.txt"]);
+  expect((await files[1]!.readToEnd()).toString()).toBe("third");
+  expect(rarPackage.diagnostics).toEqual([
+    {
+      volume: "damaged.rar",
+      offset: secondHeader,
+      message: "header size 61166 runs past the end of the volume",
+    },
+  ]);
+});
+
+test("tolerant parsing skips unsupported entries and reports them", async () => {
+  const archive = mockRarArchive([
+    { name: "packed.txt", data: Buffer.from("x"), method: 0x33 },
+    { name: "stored.txt", data: Buffer.from("y") },
+  ]);
+  const rarPackage = new RarFilesPackage([
+    new MemoryFileMedia(archive, "mixed.rar"),
+  ]);
+  await expect(rarPackage.parse()).rejects.toThrow(
+    "Decompression is not implemented"
+  );
+  const files = await rarPackage.parse({ tolerant: true });
+  expect(files.map((f) => f.name)).toEqual(["stored.txt"]);
+  expect(rarPackage.diagnostics).toEqual([
+    {
+      volume: "mixed.rar",
+      offset: 20,
+      message: "packed.txt: Decompression is not implemented",
+    },
+  ]);
+});
+
+test("rar package can parse archives embedded after other data", async () => {
+  const archive = mockRarArchive([{ name: "a.txt", data: Buffer.from("hi") }]);
+  const media = new MemoryFileMedia(
+    Buffer.concat([Buffer.alloc(5000, 0x90), archive]),
+    "blob.rar"
+  );
+  expect(await RarFilesPackage.scanForSignature(media)).toEqual({
+    offset: 5000,
+    version: 4,
+  });
+
+  const rarPackage = new RarFilesPackage([media]);
+  await expect(rarPackage.parse({ maxScanBytes: 4000 })).rejects.toThrow(
+    "No RAR signature found in the first 4000 bytes of blob.rar"
+  );
+  const [file] = await rarPackage.parse({ maxScanBytes: 8000 });
+  expect((await file!.readToEnd()).toString()).toBe("hi");
+});
+
+test("rar package skips the stub of self-extracting archives", async () => {
+  const stub = Buffer.alloc(70 * 1024, "MZ");
+  const archive = mockRarArchive([{ name: "a.txt", data: Buffer.from("sfx") }]);
+  const rarPackage = new RarFilesPackage([
+    new MemoryFileMedia(Buffer.concat([stub, archive]), "setup.exe"),
+  ]);
+  const [file] = await rarPackage.parse();
+  expect((await file!.readToEnd()).toString()).toBe("sfx");
+  expect(rarPackage.archiveInfo?.sfxStubSize).toBe(stub.length);
+});
+
+test("rar package rejects files without a RAR signature", async () => {
+  const rarPackage = new RarFilesPackage([
+    new MemoryFileMedia(Buffer.alloc(2048, "MZ"), "setup.exe"),
+  ]);
+  await expect(rarPackage.parse()).rejects.toThrow(
+    "No RAR signature found in the first 1048576 bytes of setup.exe"
+  );
+  await expect(rarPackage.parse({ maxScanBytes: 0 })).rejects.toThrow(
+    "No RAR signature found in the first 0 bytes of setup.exe"
+  );
+});
+
+test("rar package ignores .exe files that are not volumes", async () => {
+  const archive = mockRarArchive([{ name: "a.txt", data: Buffer.from("a") }]);
+  const rarPackage = new RarFilesPackage([
+    new MemoryFileMedia(Buffer.alloc(2048, "MZ"), "readme.exe"),
+    new MemoryFileMedia(archive, "a.rar"),
+  ]);
+  expect(rarPackage.rarFileBundle.fileNames).toEqual(["a.rar"]);
+  const files = await rarPackage.parse();
+  expect(files.map((f) => f.name)).toEqual(["a.txt"]);
+  expect(rarPackage.rarFileBundle.fileNames).toEqual(["a.rar"]);
+  expect(rarPackage.diagnostics).toEqual([
+    { volume: "readme.exe", offset: 0, message: "no RAR signature found, ignored" },
+  ]);
+});
+
+test("rar package errors carry a code and where they happened", async () => {
+  const archive = mockRarArchive([
+    { name: "plain.txt", data: Buffer.from("x") },
+    { name: "secret.txt", data: Buffer.from("y"), flags: 0x04 },
+  ]);
+  const rarPackage = new RarFilesPackage([
+    new MemoryFileMedia(archive, "encrypted.rar"),
+  ]);
+  const error = await rarPackage.parse().catch((e) => e);
+  expect(error).toBeInstanceOf(RarError);
+  expect({ ...error, message: error.message }).toEqual({
+    name: "RarError",
+    code: "ENCRYPTED_ENTRY",
+    message: "Decryption is not implemented",
+    volume: "encrypted.rar",
+    volumeIndex: 0,
+    offset: 7 + 13 + 32 + 9 + 1,
+    entry: "secret.txt",
+  });
+});
+
+const SERVICE_HEADER = 0x7a;
+
+const archiveWithComment = () =>
+  mockRarArchive([
+    { name: "CMT", data: Buffer.from("a comment"), type: SERVICE_HEADER },
+    { name: "a.txt", data: Buffer.from("content") },
+    { name: "RR", data: Buffer.from("recovery"), type: SERVICE_HEADER },
+  ]);
+
+test("rar package lists files after service headers", async () => {
+  const rarPackage = new RarFilesPackage([
+    new MemoryFileMedia(archiveWithComment(), "comment.rar"),
+  ]);
+  const files = await rarPackage.parse();
+  expect(files.map((f) => f.name)).toEqual(["a.txt"]);
+  expect((await files[0]!.readToEnd()).toString()).toBe("content");
+  expect(rarPackage.serviceEntries).toEqual([]);
+});
+
+test("rar package enumerates service entries on request", async () => {
+  const rarPackage = new RarFilesPackage([
+    new MemoryFileMedia(archiveWithComment(), "comment.rar"),
+  ]);
+  await rarPackage.parse({ includeServiceEntries: true });
+  const entries = rarPackage.serviceEntries;
+  expect(entries.map(({ file, ...entry }) => entry)).toEqual([
+    {
+      type: "CMT",
+      volume: "comment.rar",
+      volumeIndex: 0,
+      offset: 20,
+      packedSize: 9,
+      unpackedSize: 9,
+      method: 0x30,
+    },
+    {
+      type: "RR",
+      volume: "comment.rar",
+      volumeIndex: 0,
+      offset: 20 + 35 + 9 + 37 + 7,
+      packedSize: 8,
+      unpackedSize: 8,
+      method: 0x30,
+      owner: "a.txt",
+    },
+  ]);
+  expect((await entries[0]!.file!.readToEnd()).toString()).toBe("a comment");
+});
+
+test("rar package reads stored archive and file comments", async () => {
+  const rarPackage = new RarFilesPackage([
+    new MemoryFileMedia(
+      mockRarArchive([
+        { name: "CMT", data: Buffer.from("a comment"), type: SERVICE_HEADER },
+        { name: "a.txt", data: Buffer.from("content") },
+        { name: "CMT", data: Buffer.from("about a"), type: SERVICE_HEADER },
+        { name: "b.txt", data: Buffer.from("content") },
+        {
+          name: "CMT",
+          data: Buffer.from("packed"),
+          type: SERVICE_HEADER,
+          method: 0x33,
+        },
+      ]),
+      "comment.rar"
+    ),
+  ]);
+  const [a, b] = await rarPackage.parse();
+  expect(rarPackage.archiveInfo?.comment).toBe("a comment");
+  expect(a!.comment).toBe("about a");
+  expect(b!.comment).toBe(undefined);
+  expect(rarPackage.diagnostics).toEqual([
+    {
+      volume: "comment.rar",
+      offset: 20 + 35 + 9 + 37 + 7 + 35 + 7 + 37 + 7,
+      message: "b.txt: comment is packed or encrypted, not available",
+    },
+  ]);
+});
+
+test("rar package reads comment blocks of old archives", async () => {
+  const COMMENT_HEADER = 0x75;
+  const rarPackage = new RarFilesPackage([
+    new MemoryFileMedia(
+      mockRarArchive(
+        [
+          { name: "a.txt", data: Buffer.from("content") },
+          { name: "", data: Buffer.from("about a"), type: COMMENT_HEADER },
+          { name: "b.txt", data: Buffer.from("content") },
+        ],
+        0,
+        Buffer.from("an old comment")
+      ),
+      "old.rar"
+    ),
+  ]);
+  const [a] = await rarPackage.parse();
+  expect(rarPackage.archiveInfo?.hasComment).toBe(true);
+  expect(rarPackage.archiveInfo?.comment).toBe("an old comment");
+  expect(a!.comment).toBe("about a");
+});
+
+test("parseIter yields entries as their last volume is parsed", async () => {
+  const rarPackage = new RarFilesPackage(multipleRarFileWithManyInnerFiles);
+  const events: string[] = [];
+  rarPackage.on("file-parsed", (rarFile) => events.push(rarFile.name));
+  for await (const innerFile of rarPackage.parseIter()) {
+    events.push(innerFile.name);
+  }
+  expect(events).toEqual([
+    "multi-splitted.rar",
+    "splitted1.txt",
+    "splitted2.txt",
+    "multi-splitted.r00",
+    "splitted3.txt",
+    "multi-splitted.r01",
+    "splitted4.txt",
+  ]);
+});
+
+test("an exported index restores the inner files without parsing", async () => {
+  const rarPackage = new RarFilesPackage(multipleRarFileWithManyInnerFiles);
+  const index = JSON.parse(JSON.stringify(await rarPackage.exportIndex()));
+
+  const restoredPackage = new RarFilesPackage(multipleRarFileWithManyInnerFiles);
+  let parsed = false;
+  restoredPackage.on("parsing-start", () => (parsed = true));
+  const innerFiles = restoredPackage.loadIndex(index);
+
+  expect(parsed).toBe(false);
+  expect(innerFiles.map((f) => f.name)).toEqual([
+    "splitted1.txt",
+    "splitted2.txt",
+    "splitted3.txt",
+    "splitted4.txt",
+  ]);
+  expect(await readToEnd(innerFiles)).toEqual(
+    await readToEnd(await rarPackage.parse())
+  );
+  const [first] = await rarPackage.parse();
+  expect(innerFiles[0]!.modifiedAt).toEqual(first!.modifiedAt);
+});
+
+test("an exported index is rejected for different volumes", async () => {
+  const index = await new RarFilesPackage(
+    multipleRarFileWithManyInnerFiles
+  ).exportIndex();
+  const rarPackage = new RarFilesPackage(multipleRarFileWithOneInnerFile);
+  expect(() => rarPackage.loadIndex(index)).toThrow(
+    "Archive index does not match: expected volume 0 to be multi-splitted.rar"
+  );
+});
+
+test("an exported index is rejected when it refers to missing volumes", async () => {
+  const index = await new RarFilesPackage(
+    multipleRarFileWithOneInnerFile
+  ).exportIndex();
+  const [first] = multipleRarFileWithOneInnerFile;
+  const truncated = { ...index, volumes: index.volumes.slice(0, 1) };
+  const load = () => new RarFilesPackage([first!]).loadIndex(truncated);
+  expect(load).toThrow(RarError);
+  expect(load).toThrow(
+    "Archive index does not match: multi.txt refers to volume 1, which is not listed"
+  );
+
+  const [entry] = index.entries;
+  const chunk: [number, number, number] = [0, 0, first!.length];
+  const outOfRange = { ...index, entries: [{ ...entry!, chunks: [chunk] }] };
+  expect(() =>
+    new RarFilesPackage(multipleRarFileWithOneInnerFile).loadIndex(outOfRange)
+  ).toThrow(`multi.txt refers to bytes 0-${first!.length} of multi.rar`);
+});
+
+test("inner files map their bytes to volume ranges", async () => {
+  const [file] = await new RarFilesPackage(multipleRarFileWithOneInnerFile).parse();
+  const multiFile = fs.readFileSync(multiFilePath);
+
+  const locations = [...file!.locations()];
+  expect(locations.map((l) => l.volume.name)).toEqual([
+    "multi.rar",
+    "multi.r00",
+    "multi.r01",
+  ]);
+  expect(locations.map((l) => l.volumeIndex)).toEqual([0, 1, 2]);
+  expect(locations.reduce((length, l) => length + l.length, 0)).toBe(file!.length);
+
+  const second = locations[1]!;
+  const location = file!.locate(second.fileOffset + 10);
+  expect(location.volume).toBe(second.volume);
+  expect(location.offset).toBe(second.offset + 10);
+  expect(location.length).toBe(second.length - 10);
+  const stored = await streamToBuffer(
+    await location.volume.createReadStream({
+      start: location.offset,
+      end: location.offset + 99,
+    })
+  );
+  expect(stored).toEqual(
+    multiFile.subarray(location.fileOffset, location.fileOffset + 100)
+  );
+  expect(() => file!.locate(file!.length)).toThrow("Illegal offset");
+});
+
+test("inner files report the volumes a range needs", async () => {
+  const [file] = await new RarFilesPackage(multipleRarFileWithOneInnerFile).parse();
+  const [first, second, third] = [...file!.locations()];
+
+  const names = (ranges: ReturnType<InnerFile["requiredVolumes"]>) =>
+    ranges.map((r) => r.volume.name);
+  expect(names(file!.requiredVolumes())).toEqual([
+    "multi.rar",
+    "multi.r00",
+    "multi.r01",
+  ]);
+  const ranges = file!.requiredVolumes({
+    start: second!.fileOffset + 5,
+    end: third!.fileOffset + 9,
+  });
+  expect(ranges.map(({ volume, ...range }) => range)).toEqual([
+    {
+      volumeIndex: 1,
+      start: second!.offset + 5,
+      end: second!.offset + second!.length - 1,
+    },
+    { volumeIndex: 2, start: third!.offset, end: third!.offset + 9 },
+  ]);
+  expect(names(file!.requiredVolumes({ start: 0, end: first!.length - 1 }))).toEqual([
+    "multi.rar",
+  ]);
+  // ranges past the end are rejected, as when streaming
+  const pastEnd = { start: third!.fileOffset, end: file!.length + 100 };
+  expect(() => file!.requiredVolumes(pastEnd)).toThrow(
+    "Illegal start/end offset"
+  );
+  await expect(file!.prefetch(pastEnd)).rejects.toThrow(
+    "Illegal start/end offset"
+  );
+});
+
+test("rar package decodes unicode and codepage names", async () => {
+  const archive = mockRarArchive([
+    {
+      name: "",
+      nameBytes: Buffer.from("3f6100" + "03" + "40" + "a961", "hex"),
+      flags: 0x200,
+    },
+    { name: "", nameBytes: Buffer.from([0xc0, 0xeb]) },
+  ]);
+  const parse = (opts: FindOpts = {}) =>
+    new RarFilesPackage([new MemoryFileMedia(archive, "names.rar")]).parse(opts);
+  const names = async (opts: FindOpts = {}) =>
+    (await parse(opts)).map((f) => f.name);
+
+  expect(await names()).toEqual(["Ωa", "��"]);
+  expect(await names({ nameEncoding: "windows-1251" })).toEqual(["Ωa", "Ал"]);
+  expect(
+    await names({ nameEncoding: (bytes: Buffer) => bytes.toString("hex") })
+  ).toEqual(["Ωa", "c0eb"]);
+  await expect(parse({ nameEncoding: "klingon" })).rejects.toThrow(
+    'Unknown name encoding "klingon", expected a TextDecoder label such as "windows-1251"'
+  );
+
+  const [, file] = await parse();
+  expect(file!.nameBytes).toEqual(Buffer.from([0xc0, 0xeb]));
+});
+
+test("inner files use the extended times when present", async () => {
+  const extendedTime = Buffer.alloc(12);
+  extendedTime.writeUInt16LE(0xf0f0, 0);
+  extendedTime.writeUIntLE(0x123456, 2, 3);
+  extendedTime.writeUInt32LE(0x495a9ad0, 5);
+  extendedTime.writeUIntLE(0x0f4240, 9, 3);
+  const [file] = await new RarFilesPackage([
+    new MemoryFileMedia(
+      mockRarArchive([
+        {
+          name: "a.txt",
+          flags: 0x1000,
+          timestamp: 0x495a9ad3,
+          subData: extendedTime,
+        },
+      ]),
+      "times.rar"
+    ),
+  ]).parse();
+
+  // 2016-10-26 19:22:38 local time plus a second and 119.3046 ms
+  expect(file!.modifiedAt!.getTime()).toBe(
+    new Date(2016, 9, 26, 19, 22, 39).getTime() + 119
+  );
+  expect(file!.accessedAt!.getTime()).toBe(
+    new Date(2016, 9, 26, 19, 22, 33).getTime() + 100
+  );
+  expect(file!.createdAt).toBe(undefined);
+});
+
+test("inner files keep long names and their extended times", async () => {
+  const name = `${"d".repeat(200)}/${"f".repeat(100)}.txt`;
+  const extendedTime = Buffer.alloc(2);
+  extendedTime.writeUInt16LE(0xc000, 0);
+  const [file] = await new RarFilesPackage([
+    new MemoryFileMedia(
+      mockRarArchive([
+        { name, flags: 0x1000, timestamp: 0x495a9ad3, subData: extendedTime },
+      ]),
+      "long.rar"
+    ),
+  ]).parse();
+
+  expect(file!.name).toBe(name);
+  // 2016-10-26 19:22:38 local time plus a second
+  expect(file!.modifiedAt!.getTime()).toBe(
+    new Date(2016, 9, 26, 19, 22, 39).getTime()
+  );
+});
+
+// A volume that is all zeros except for the given parts, so multi gigabyte
+// archives can be tested without allocating them.
+const sparseMedia = (
+  name: string,
+  length: number,
+  parts: [number, Buffer][]
+): IFileMedia => ({
+  name,
+  length,
+  createReadStream({ start, end } = { start: 0, end: length - 1 }) {
+    const buffer = Buffer.alloc(end - start + 1);
+    for (const [offset, data] of parts) {
+      const from = Math.max(start, offset);
+      const to = Math.min(end + 1, offset + data.length);
+      if (from < to) {
+        data.copy(buffer, from - start, from - offset, to - offset);
+      }
+    }
+    return Readable.from([buffer]);
+  },
+});
+
+const largeArchive = (size: number, volumeLength?: number) => {
+  const archive = mockRarArchive([{ name: "big.bin", size }]);
+  // everything but the end of archive block
+  const dataOffset = archive.length - 7;
+  const length = volumeLength ?? dataOffset + size + 7;
+  return {
+    dataOffset,
+    media: sparseMedia("big.rar", length, [
+      [0, archive.subarray(0, dataOffset)],
+      [dataOffset + size - 4, Buffer.from("tail")],
+      [dataOffset + size, archive.subarray(dataOffset)],
+    ]),
+  };
+};
+
+test("rar package handles entries larger than 4 GiB", async () => {
+  const size = 5 * 1024 * 1024 * 1024 + 3;
+  const { media, dataOffset } = largeArchive(size);
+  const [file] = await new RarFilesPackage([media]).parse({ tolerant: true });
+
+  expect(file!.length).toBe(size);
+  expect(file!.locate(size - 1)).toEqual({
+    volume: media,
+    volumeIndex: 0,
+    fileOffset: size - 1,
+    offset: dataOffset + size - 1,
+    length: 1,
+  });
+  const tail = await file!.createReadStream({ start: size - 4, end: size - 1 });
+  expect((await streamToBuffer(tail)).toString()).toBe("tail");
+});
+
+test("tolerant parsing checks the high size word of file data", async () => {
+  // the low word alone would fit in the volume
+  const size = 0x100000000 + 100;
+  const { media } = largeArchive(size, 1024 * 1024);
+  const rarPackage = new RarFilesPackage([media]);
+  expect(await rarPackage.parse({ tolerant: true })).toEqual([]);
+  expect(rarPackage.diagnostics.map((d) => d.message)).toEqual([
+    `data size ${size} runs past the end of the volume`,
+  ]);
+});
+
+test("rar package reports header types from newer RAR versions", async () => {
+  const SKIP_IF_UNKNOWN = 0x4000;
+  const archive = (flags: number) =>
+    new MemoryFileMedia(
+      mockRarArchive([
+        { name: "NEW", type: 0x7c, flags, data: Buffer.from("?") },
+        { name: "a.txt", data: Buffer.from("a") },
+      ]),
+      "new.rar"
+    );
+  const error = await new RarFilesPackage([archive(0)]).parse().catch((e) => e);
+  expect(error).toBeInstanceOf(RarError);
+  expect(error.code).toBe("UNSUPPORTED_FEATURE");
+  expect(error.feature).toBe("header type 0x7c");
+  expect(error.message).toBe(
+    "Unknown header type 0x7c in new.rar at offset 20, a newer RAR version is needed"
+  );
+
+  const tolerant = new RarFilesPackage([archive(0)]);
+  const files = await tolerant.parse({ tolerant: true });
+  expect(files.map((f) => f.name)).toEqual(["a.txt"]);
+  expect(tolerant.diagnostics.length).toBe(1);
+
+  const skippable = await new RarFilesPackage([archive(SKIP_IF_UNKNOWN)]).parse();
+  expect(skippable.map((f) => f.name)).toEqual(["a.txt"]);
+});
+
+test("rar package reports the RAR version compressed entries need", async () => {
+  const rarPackage = new RarFilesPackage([
+    new MemoryFileMedia(
+      mockRarArchive([{ name: "a.txt", method: 0x33, version: 50 }]),
+      "future.rar"
+    ),
+  ]);
+  await expect(rarPackage.parse()).rejects.toThrow("a.txt needs RAR 5.0 to extract");
+});
+
+test("rar package describes how entries are compressed", async () => {
+  const rarPackage = new RarFilesPackage([
+    new MemoryFileMedia(
+      mockRarArchive([
+        { name: "stored.txt", data: Buffer.from("x"), host: 2, version: 29 },
+        {
+          name: "packed.txt",
+          data: Buffer.from("x"),
+          method: 0x35,
+          flags: 0x10 | 0x80,
+        },
+      ]),
+      "methods.rar"
+    ),
+  ]);
+  const seen: unknown[] = [];
+  const [stored, ...rest] = await rarPackage.parse({
+    filter: (name, _, compression) => {
+      seen.push([name, compression.method, compression.dictionarySize]);
+      return compression.method === "store";
+    },
+  });
+  expect(rest).toEqual([]);
+  expect(seen).toEqual([
+    ["stored.txt", "store", 0x10000],
+    ["packed.txt", "best", 0x100000],
+  ]);
+  expect(stored!.compression).toEqual({
+    method: "store",
+    dictionarySize: 0x10000,
+    isSolid: false,
+    isEncrypted: false,
+    host: "windows",
+    unpackVersion: 29,
+  });
+});
+
+test("rar package ignores duplicate volumes and reports them", async () => {
+  const [first, second, third] = multipleRarFileWithManyInnerFiles;
+  const rarPackage = new RarFilesPackage([first!, second!, second!, third!]);
+  const files = await rarPackage.parse();
+  expect(files.map((f) => f.name)).toEqual([
+    "splitted1.txt",
+    "splitted2.txt",
+    "splitted3.txt",
+    "splitted4.txt",
+  ]);
+  expect(rarPackage.diagnostics).toEqual([
+    {
+      volume: "multi-splitted.r00",
+      offset: 0,
+      message: "duplicate volume, ignored",
+    },
+  ]);
+});
+
+test("rar package refuses volumes of different sets", async () => {
+  const archive = mockRarArchive([{ name: "a.txt", data: Buffer.from("a") }]);
+  const rarPackage = new RarFilesPackage([
+    new MemoryFileMedia(archive, "a.rar"),
+    new MemoryFileMedia(archive, "b.rar"),
+  ]);
+  const error = await rarPackage.parse().catch((e) => e);
+  expect(error).toBeInstanceOf(RarError);
+  expect(error.code).toBe("VOLUME_MISMATCH");
+  expect(error.message).toMatch(/^[ab]\.rar and [ab]\.rar belong to different/);
+});
+
+const volumeSet = (...fixtures: string[]) =>
+  fixtures.map(
+    (fixture, i) =>
+      new MemoryFileMedia(
+        fs.readFileSync(path.resolve(fixturePath, fixture)),
+        i === 0 ? "set.rar" : `set.r0${i - 1}`
+      )
+  );
+
+test("validateVolumes also parses volumes covered by a single entry", async () => {
+  const volumes = volumeSet("multi/multi.rar", "multi/multi.r00", "multi/multi.r01");
+  const [file] = await new RarFilesPackage(volumes).parse({
+    validateVolumes: true,
+  });
+  expect(await file!.readToEnd()).toEqual(fs.readFileSync(multiFilePath));
+});
+
+test("rar package rejects volumes from other archives", async () => {
+  const parse = (...fixtures: string[]) =>
+    new RarFilesPackage(volumeSet(...fixtures)).parse({ validateVolumes: true });
+
+  const error = await parse(
+    "multi/multi.rar",
+    "multi-splitted/multi-splitted.r00",
+    "multi/multi.r01"
+  ).catch((e) => e);
+  expect(error).toBeInstanceOf(RarError);
+  expect(error.code).toBe("VOLUME_MISMATCH");
+  expect(error.message).toBe(
+    "Volume set.r00 does not belong to this archive: expected the continuation of multi.txt, found a new entry"
+  );
+  await expect(
+    parse("multi/multi.rar", "single/single.rar", "multi/multi.r01")
+  ).rejects.toThrow(
+    "Volume set.r00 does not belong to this archive: it is not part of a multi-volume archive"
+  );
+  await expect(
+    parse("multi-splitted/multi-splitted.rar", "multi/multi.r00")
+  ).rejects.toThrow(
+    "Volume set.r00 does not belong to this archive: expected a new entry, found the continuation of multi.txt"
+  );
+});
+
+const volumesNamed = (volumes: Record<string, string>) =>
+  Object.entries(volumes).map(
+    ([name, fixture]) =>
+      new MemoryFileMedia(
+        fs.readFileSync(path.resolve(fixturePath, fixture)),
+        name
+      )
+  );
+
+test("rar package reports missing volumes", async () => {
+  const volumes = volumesNamed({
+    "set.rar": "multi-splitted/multi-splitted.rar",
+    "set.r01": "multi-splitted/multi-splitted.r01",
+  });
+  const error = await new RarFilesPackage(volumes).parse().catch((e) => e);
+  expect(error).toBeInstanceOf(RarError);
+  expect(error.code).toBe("MISSING_VOLUME");
+  expect(error.message).toBe("Missing volume set.r00 before set.r01");
+});
+
+test("allowMissingVolumes returns the entries outside of the gap", async () => {
+  const volumes = volumesNamed({
+    "set.rar": "multi-splitted/multi-splitted.rar",
+    "set.r01": "multi-splitted/multi-splitted.r01",
+  });
+  const rarPackage = new RarFilesPackage(volumes);
+  const files = await rarPackage.parse({ allowMissingVolumes: true });
+  expect(files.map((f) => [f.name, f.incomplete])).toEqual([
+    ["splitted1.txt", false],
+    ["splitted2.txt", false],
+  ]);
+  expect(rarPackage.diagnostics).toEqual([
+    {
+      volume: "set.r01",
+      offset: 20,
+      message: "splitted4.txt: starts in a missing volume, skipped",
+    },
+  ]);
+});
+
+test("allowMissingVolumes reads incomplete entries up to the gap", async () => {
+  const volumes = volumesNamed({
+    "set.rar": "multi/multi.rar",
+    "set.r01": "multi/multi.r01",
+  });
+  const [file, ...rest] = await new RarFilesPackage(volumes).parse({
+    allowMissingVolumes: true,
+  });
+  expect(rest).toEqual([]);
+  expect(file!.incomplete).toBe(true);
+  expect(file!.missingVolumes).toEqual(["set.r00"]);
+  const content = fs.readFileSync(multiFilePath);
+  expect(await file!.readToEnd()).toEqual(content.subarray(0, file!.length));
+  expect(file!.length).toBeLessThan(content.length);
+});
+
+test("entries continuing past the last volume are incomplete", async () => {
+  const volumes = volumesNamed({ "set.rar": "multi/multi.rar" });
+  const [file] = await new RarFilesPackage(volumes).parse();
+  expect(file!.incomplete).toBe(true);
+  expect(file!.missingVolumes).toEqual(["set.r00"]);
+});
+
+test("entries continuing past the last volume name are incomplete", async () => {
+  // the old naming scheme has no volume after .z99
+  const volumes = volumesNamed({ "set.z99": "multi/multi.rar" });
+  const [file] = await new RarFilesPackage(volumes).parse();
+  expect(file!.incomplete).toBe(true);
+  expect(file!.missingVolumes).toEqual([]);
+  await expect(file!.verify()).rejects.toThrow(
+    "Cannot verify multi.txt, it continues past the last volume"
+  );
+});
+
+test("archive indexes keep entries incomplete", async () => {
+  const volumes = volumesNamed({ "set.rar": "multi/multi.rar" });
+  const rarPackage = new RarFilesPackage(volumes);
+  const index = JSON.parse(JSON.stringify(await rarPackage.exportIndex()));
+  const [file] = rarPackage.loadIndex(index);
+  expect(file!.incomplete).toBe(true);
+  expect(file!.missingVolumes).toEqual(["set.r00"]);
+  expect(file!.crc32).toBe(undefined);
+});
+
+test("incomplete entries cannot be verified", async () => {
+  const volumes = volumesNamed({ "set.rar": "multi/multi.rar" });
+  const rarPackage = new RarFilesPackage(volumes);
+  const [file] = await rarPackage.parse({ verify: true });
+  const error = await file!.verify().catch((e) => e);
+  expect(error).toBeInstanceOf(RarError);
+  expect(error.code).toBe("MISSING_VOLUME");
+  expect(error.message).toBe("Cannot verify multi.txt, missing volume set.r00");
+  await expect(file!.readToEnd()).rejects.toThrow("Cannot verify multi.txt");
+});
+
+test("visitHeaders visits the file headers of every volume", async () => {
+  const rarPackage = new RarFilesPackage(multipleRarFileWithOneInnerFile);
+  const headers: string[] = [];
+  await rarPackage.visitHeaders({
+    file: (header, { volume }) => {
+      headers.push(`${path.basename(volume.name)}: ${header.name}`);
+    },
+  });
+  expect(headers).toEqual([
+    "multi.rar: multi.txt",
+    "multi.r00: multi.txt",
+    "multi.r01: multi.txt",
+  ]);
+});
+
+test("stats count split entries once with the data of every part", async () => {
+  const stats = await new RarFilesPackage(multipleRarFileWithOneInnerFile).stats();
+  const size = fs.statSync(multiFilePath).size;
+  expect(stats).toMatchObject({
+    count: 1,
+    unpackedSize: size,
+    packedSize: size,
+    ratio: 1,
+    methods: { store: { count: 1, packedSize: size, unpackedSize: size } },
+    largest: [{ name: "multi.txt", packedSize: size, unpackedSize: size }],
+  });
+});
+
+test("stats break down compressed and encrypted entries", async () => {
+  const media = new MemoryFileMedia(
+    mockRarArchive([
+      { name: "docs", flags: 0xe0 },
+      { name: "a.txt", data: Buffer.alloc(10) },
+      { name: "b.bin", data: Buffer.alloc(30), method: 0x33 },
+      { name: "c.bin", data: Buffer.alloc(20), method: 0x35, flags: 0x14 },
+    ]),
+    "packed.rar"
+  );
+  const rarPackage = new RarFilesPackage([media]);
+  await expect(rarPackage.parse()).rejects.toThrow(RarError);
+  const stats = await rarPackage.stats({}, 2);
+  expect(stats).toMatchObject({
+    count: 3,
+    directoryCount: 1,
+    packedSize: 60,
+    encryptedCount: 1,
+    solidCount: 1,
+    methods: {
+      store: { count: 1, packedSize: 10 },
+      normal: { count: 1, packedSize: 30 },
+      best: { count: 1, packedSize: 20 },
+    },
+  });
+  expect(stats.largest.map((e) => e.name)).toEqual(["b.bin", "c.bin"]);
+});
+
+test("stored archives inside an archive can be parsed from their inner files", async () => {
+  const subs = mockRarArchive([
+    { name: "movie.srt", data: Buffer.from("1\n00:00:01,000 --> hello") },
+  ]);
+  const volumes = ["multi.rar", "multi.r00", "multi.r01"].map((name) => ({
+    name: `Sample/${name}`,
+    data: fs.readFileSync(path.resolve(fixturePath, "multi", name)),
+  }));
+  const release = await new RarFilesPackage([
+    new MemoryFileMedia(
+      mockRarArchive([{ name: "Subs/subs.rar", data: subs }, ...volumes]),
+      "release.rar"
+    ),
+  ]).parse();
+
+  const [srt] = await new RarFilesPackage([release[0]!]).parse();
+  expect(srt!.name).toBe("movie.srt");
+  expect((await srt!.readToEnd()).toString()).toBe("1\n00:00:01,000 --> hello");
+
+  const [multi] = await new RarFilesPackage(release.slice(1)).parse();
+  await multi!.verify();
+  expect(await multi!.readToEnd()).toEqual(fs.readFileSync(multiFilePath));
+});
+
+test("inner files reject reads past their last byte", async () => {
+  const [file] = await new RarFilesPackage([
+    new MemoryFileMedia(
+      mockRarArchive([{ name: "a.txt", data: Buffer.from("hello") }]),
+      "a.rar"
+    ),
+  ]).parse();
+  expect(() => file!.createReadStream({ start: 3, end: 100 })).toThrow(
+    "Illegal start/end offset"
+  );
+  const stream = await file!.createReadStream({ start: 3, end: 4 });
+  expect((await streamToBuffer(stream)).toString()).toBe("lo");
+});
+
+test("inner files sniff their content type once", async () => {
+  const png = Buffer.concat([
+    Buffer.from("89504e470d0a1a0a", "hex"),
+    Buffer.alloc(10000),
+  ]);
+  const media = new MemoryFileMedia(
+    mockRarArchive([{ name: "image.dat", data: png }]),
+    "images.rar"
+  );
+  const reads: number[] = [];
+  const createReadStream = media.createReadStream.bind(media);
+  media.createReadStream = (interval) => {
+    reads.push(interval.end - interval.start + 1);
+    return createReadStream(interval);
+  };
+  const [file] = await new RarFilesPackage([media]).parse();
+  reads.length = 0;
+  expect(await file!.sniffContentType()).toBe("image/png");
+  expect(await file!.sniffContentType()).toBe("image/png");
+  expect(reads).toEqual([4096]);
+});
+
+test("inner files read their first and last bytes", async () => {
+  const [file] = await new RarFilesPackage(
+    multipleRarFileWithOneInnerFile
+  ).parse();
+  const content = fs.readFileSync(multiFilePath);
+  expect(await file!.readHead(100)).toEqual(content.subarray(0, 100));
+  expect(await file!.readTail(1000)).toEqual(content.subarray(-1000));
+  expect(await file!.readHead()).toEqual(content);
+  expect(await file!.readTail(0)).toEqual(Buffer.alloc(0));
+});
@@ -1,48 +1,221 @@
 import { EventEmitter } from "events";
-import { makeRarFileBundle, RarFileBundle } from "./rar-file-bundle.js";
+import { readdirSync } from "fs";
+import { basename, dirname, join } from "path";
+import { debuglog } from "util";
+import {
+  findVolumeNames,
+  makeRarFileBundle,
+  RarFileBundle,
+  volumeSetName,
+} from "./rar-file-bundle.js";
 import { RarFileChunk } from "./rar-file-chunk.js";
 import { InnerFile } from "./inner-file.js";
+import { buildTree } from "./inner-file-tree.js";
+import { extractFiles } from "./extract.js";
+import { createIndex, IArchiveIndex, restoreIndex } from "./archive-index.js";
+import { archiveStats } from "./archive-stats.js";
+import { describeCompression } from "./compression-info.js";
+import { IHeaderVisitor, visitHeaders } from "./header-visitor.js";
+import {
+  findNextHeader,
+  findSignature,
+  readRange,
+  validateHeader,
+} from "./header-scan.js";
+import { LocalFileMedia } from "./local-file-media.js";
 
 import { MarkerHeaderParser } from "./parsing/marker-header-parser.js";
 import { ArchiveHeaderParser } from "./parsing/archive-header-parser.js";
 import { FileHeaderParser, IFileHeader } from "./parsing/file-header-parser.js";
 import { TerminatorHeaderParser } from "./parsing/terminator-header-parser.js";
 
-import { streamToBuffer } from "./stream-utils.js";
-import { IFileMedia, IParser, IParsers, FindOpts } from "./interfaces.js";
-import { groupBy, mapValues } from "./utils.js";
+import {
+  IArchiveInfo,
+  IExtractOpts,
+  IFileMedia,
+  ILimits,
+  IParseDiagnostic,
+  ITestResult,
+  IParser,
+  IServiceEntry,
+  IParsers,
+  FindOpts,
+} from "./interfaces.js";
+import { RarError } from "./rar-error.js";
+
+const debug = debuglog("rar-stream");
 
 const parseHeader = async <T extends IParsers>(
   Parser: IParser<T>,
   fileMedia: IFileMedia,
   offset = 0
 ) => {
-  const stream = await fileMedia.createReadStream({
-    start: offset,
-    end: offset + Parser.HEADER_SIZE,
-  });
-  const headerBuffer = await streamToBuffer(stream);
+  let headerBuffer = await readRange(
+    fileMedia,
+    offset,
+    offset + Parser.HEADER_SIZE
+  );
+  // long names and extra fields make headers longer than the usual read
+  const headSize =
+    headerBuffer.length >= BASE_HEADER_SIZE ? headerBuffer.readUInt16LE(5) : 0;
+  if (headSize > headerBuffer.length) {
+    headerBuffer = await readRange(fileMedia, offset, offset + headSize - 1);
+  }
   const parser = new Parser(headerBuffer);
   return parser.parse() as ReturnType<T["parse"]>;
 };
+const BASE_HEADER_SIZE = 7;
+const FILE_HEADER_FIXED_SIZE = 32;
+const FILE_HEADER_TYPE = 0x74;
+const SERVICE_HEADER_TYPE = 0x7a;
+const LONG_BLOCK_FLAG = 0x8000;
+const SKIP_IF_UNKNOWN_FLAG = 0x4000;
+// marker up to end of archive, anything else comes from a newer RAR
+const KNOWN_HEADER_TYPES = new Set([
+  0x72, 0x73, 0x74, 0x75, 0x76, 0x77, 0x78, 0x79, 0x7a, 0x7b,
+]);
+// RAR 3.6 is the last unpack version of the RAR 1.5-4.x format
+const MAX_UNPACK_VERSION = 36;
+const STREAM_SERVICE_NAME = "STM";
+const COMMENT_SERVICE_NAME = "CMT";
+// comments before RAR 3 have a block of their own, embedded in the archive
+// header for archive comments
+const COMMENT_HEADER_TYPE = 0x75;
+const COMMENT_HEADER_SIZE = 13;
+const STORE_METHOD = 0x30;
+// old style comment, av, sub, protect and sign blocks, plus service blocks
+const SKIPPABLE_HEADER_TYPES = new Set([0x75, 0x76, 0x77, 0x78, 0x79, 0x7a]);
+
+const MAX_SFX_STUB_SIZE = 1024 * 1024;
+
+const checkLimits = (
+  fileHead: IFileHeader,
+  context: { volume: string; volumeIndex: number; offset: number },
+  limits: ILimits,
+  countFiles: number
+) => {
+  const exceeds = (what: string, value: number, limit?: number) => {
+    if (limit !== undefined && value > limit) {
+      throw new RarError(
+        "LIMIT_EXCEEDED",
+        `Limit exceeded in ${context.volume}: ${what} ${value} is above ${limit}`,
+        { ...context, entry: fileHead.name }
+      );
+    }
+  };
+  exceeds("header size", fileHead.headSize, limits.maxHeaderSize);
+  exceeds("name length", fileHead.nameSize, limits.maxNameLength);
+  exceeds("unpacked size", fileHead.unpackedSize, limits.maxUnpackedSize);
+  if (!fileHead.continuesFromPrevious) {
+    exceeds("file count", countFiles + 1, limits.maxFileCount);
+  }
+};
+
+// names without the unicode flag are in the OEM or ANSI codepage of the packer
+const nameDecoder = (encoding: NonNullable<FindOpts["nameEncoding"]>) => {
+  if (typeof encoding === "function") {
+    return encoding;
+  }
+  let decoder: TextDecoder;
+  try {
+    decoder = new TextDecoder(encoding);
+  } catch {
+    throw new RangeError(
+      `Unknown name encoding "${encoding}", expected a TextDecoder label such as "windows-1251"`
+    );
+  }
+  return (nameBytes: Buffer) => decoder.decode(nameBytes);
+};
+
+interface IPositionedResult {
+  volumeIndex: number;
+  offset: number;
+  result: ITestResult;
+}
 interface ParsedFileChunkMapping {
   name: string;
   chunk: RarFileChunk;
-}
-interface FileChunkMapping extends ParsedFileChunkMapping {
   fileHead: IFileHeader;
 }
+interface ParseState {
+  countFiles: number;
+  selected: Set<string>;
+  isDone: boolean;
+  // the entry the previous volume ended in the middle of
+  continuing?: string | undefined;
+  // volumes missing before the current one, and whether the one right
+  // before it is missing
+  missingVolumes: number;
+  afterGap: boolean;
+  // file comments by entry name
+  comments: Map<string, string>;
+  // built once from opts.nameEncoding
+  decodeName?: ((nameBytes: Buffer) => string) | undefined;
+}
 
 export class RarFilesPackage extends EventEmitter {
   rarFileBundle: RarFileBundle;
+  archiveInfo?: IArchiveInfo;
+  diagnostics: IParseDiagnostic[] = [];
+  serviceEntries: IServiceEntry[] = [];
+  // .exe files given without a RAR signature
+  private ignoredVolumes: IFileMedia[] = [];
+  // set by test, which reports entries that cannot be read one by one
+  private entryErrors: IPositionedResult[] | undefined;
   constructor(fileMedias: IFileMedia[]) {
     super();
     this.rarFileBundle = makeRarFileBundle(fileMedias);
   }
-  async parseFile(rarFile: IFileMedia, opts: FindOpts) {
-    const fileChunks: FileChunkMapping[] = [];
+  static fromFirstVolume(volumePath: string) {
+    const dir = dirname(volumePath);
+    const volumeNames = findVolumeNames(basename(volumePath), readdirSync(dir));
+    if (!volumeNames.length) {
+      throw new Error(`No rar volumes found for ${volumePath}`);
+    }
+    return new RarFilesPackage(
+      volumeNames.map((name) => new LocalFileMedia(join(dir, name)))
+    );
+  }
+  async parseFile(
+    rarFile: IFileMedia,
+    opts: FindOpts,
+    state: ParseState,
+    volumeIndex = 0
+  ) {
+    const fileChunks: ParsedFileChunkMapping[] = [];
     let fileOffset = 0;
-    const markerHead = await parseHeader(MarkerHeaderParser, rarFile);
+    debug(
+      "volume %d: parsing %s (%d bytes)",
+      volumeIndex,
+      rarFile.name,
+      rarFile.length
+    );
+    const context = { volume: rarFile.name, volumeIndex };
+    let owner: string | undefined;
+    let markerHead = await parseHeader(MarkerHeaderParser, rarFile);
+    if (!markerHead.isValid) {
+      // self-extracting archives start with an executable stub
+      const maxScanBytes = opts.maxScanBytes ?? MAX_SFX_STUB_SIZE;
+      const signature = await findSignature(rarFile, maxScanBytes);
+      if (!signature) {
+        throw new RarError(
+          "NO_SIGNATURE",
+          `No RAR signature found in the first ${maxScanBytes} bytes of ${rarFile.name}`,
+          context
+        );
+      }
+      if (signature.version === 5) {
+        throw new RarError(
+          "UNSUPPORTED_VERSION",
+          `${rarFile.name} is a RAR 5 archive, which is not supported`,
+          { ...context, offset: signature.offset }
+        );
+      }
+      debug("volume %d: signature found at %d", volumeIndex, signature.offset);
+      fileOffset = signature.offset;
+      markerHead = await parseHeader(MarkerHeaderParser, rarFile, fileOffset);
+    }
+    const sfxStubSize = fileOffset;
     fileOffset += markerHead.size;
 
     const archiveHeader = await parseHeader(
@@ -51,18 +224,238 @@ export class RarFilesPackage extends EventEmitter {
       fileOffset
     );
     fileOffset += archiveHeader.size;
+    if (archiveHeader.isBlockEncoded) {
+      throw new RarError(
+        "ENCRYPTED_HEADERS",
+        `Archive ${rarFile.name} has encrypted headers, which is not supported`,
+        context
+      );
+    }
+    if (volumeIndex === 0) {
+      this.archiveInfo = {
+        isMultiVolume: archiveHeader.hasVolumeAttributes,
+        isSolid: archiveHeader.hasSolidAttributes,
+        isLocked: archiveHeader.isLocked,
+        hasRecovery: archiveHeader.hasRecovery,
+        hasComment: archiveHeader.hasComment,
+        hasEncryptedHeaders: archiveHeader.isBlockEncoded,
+        isNewNameScheme: archiveHeader.isNewNameScheme,
+        volumeCount: this.rarFileBundle.length,
+        sfxStubSize,
+      };
+      if (
+        archiveHeader.hasComment &&
+        archiveHeader.size >=
+          ArchiveHeaderParser.HEADER_SIZE + COMMENT_HEADER_SIZE
+      ) {
+        await this.addComment(
+          rarFile,
+          fileOffset - archiveHeader.size + ArchiveHeaderParser.HEADER_SIZE,
+          state
+        );
+      }
+    }
+    if (volumeIndex > 0 && archiveHeader.isFirstVolume) {
+      throw new RarError(
+        "VOLUME_ORDER",
+        `Volume ${rarFile.name} is out of order: expected volume ${volumeIndex}, found the first volume`,
+        context
+      );
+    }
+    if (volumeIndex > 0 && !archiveHeader.hasVolumeAttributes) {
+      throw new RarError(
+        "VOLUME_MISMATCH",
+        `Volume ${rarFile.name} does not belong to this archive: it is not part of a multi-volume archive`,
+        context
+      );
+    }
+    let isFirstFileHeader = true;
 
-    let countFiles = 0;
-    let retrievedFiles = 0;
     while (fileOffset < rarFile.length - TerminatorHeaderParser.HEADER_SIZE) {
+      if (opts.tolerant) {
+        const problem = await validateHeader(rarFile, fileOffset);
+        if (problem) {
+          this.diagnose(rarFile, fileOffset, problem);
+          const nextOffset = await findNextHeader(rarFile, fileOffset + 1);
+          if (nextOffset === undefined) {
+            break;
+          }
+          fileOffset = nextOffset;
+          continue;
+        }
+      }
       const fileHead = await parseHeader(FileHeaderParser, rarFile, fileOffset);
-      if (fileHead.type !== 116) {
+      if (fileHead.headSize < BASE_HEADER_SIZE) {
+        throw new RarError(
+          "INVALID_HEADER",
+          `Invalid header in ${rarFile.name} at offset ${fileOffset}: header size ${fileHead.headSize}`,
+          { ...context, offset: fileOffset }
+        );
+      }
+      if (SKIPPABLE_HEADER_TYPES.has(fileHead.type)) {
+        if (
+          fileHead.type === COMMENT_HEADER_TYPE ||
+          (fileHead.type === SERVICE_HEADER_TYPE &&
+            fileHead.name === COMMENT_SERVICE_NAME)
+        ) {
+          await this.addComment(
+            rarFile,
+            fileOffset,
+            state,
+            owner,
+            fileHead.type === SERVICE_HEADER_TYPE ? fileHead : undefined
+          );
+        }
+        if (
+          fileHead.type === SERVICE_HEADER_TYPE &&
+          opts.includeServiceEntries
+        ) {
+          this.serviceEntries.push(
+            await this.serviceEntry(
+              fileHead,
+              rarFile,
+              volumeIndex,
+              fileOffset,
+              owner
+            )
+          );
+        }
+        debug(
+          "volume %d: skipping header type 0x%s at %d",
+          volumeIndex,
+          fileHead.type.toString(16),
+          fileOffset
+        );
+        fileOffset += fileHead.headSize;
+        if (fileHead.flags & LONG_BLOCK_FLAG) {
+          fileOffset += fileHead.size;
+        }
+        continue;
+      }
+      if (!KNOWN_HEADER_TYPES.has(fileHead.type)) {
+        const type = `0x${fileHead.type.toString(16)}`;
+        if (!(fileHead.flags & SKIP_IF_UNKNOWN_FLAG)) {
+          const error = new RarError(
+            "UNSUPPORTED_FEATURE",
+            `Unknown header type ${type} in ${rarFile.name} at offset ${fileOffset}, a newer RAR version is needed`,
+            { ...context, offset: fileOffset, feature: `header type ${type}` }
+          );
+          if (!opts.tolerant) {
+            throw error;
+          }
+          this.diagnose(rarFile, fileOffset, error.message);
+        }
+        debug(
+          "volume %d: skipping unknown header type %s at %d",
+          volumeIndex,
+          type,
+          fileOffset
+        );
+        fileOffset += fileHead.headSize;
+        if (fileHead.flags & LONG_BLOCK_FLAG) {
+          fileOffset += fileHead.size;
+        }
+        continue;
+      }
+      if (fileHead.type !== FILE_HEADER_TYPE) {
+        debug(
+          "volume %d: header type 0x%s at %d ends the file list",
+          volumeIndex,
+          fileHead.type.toString(16),
+          fileOffset
+        );
         break;
       }
+      if (fileHead.headSize < FILE_HEADER_FIXED_SIZE) {
+        throw new RarError(
+          "INVALID_HEADER",
+          `Invalid file header in ${rarFile.name} at offset ${fileOffset}: header size ${fileHead.headSize}`,
+          { ...context, offset: fileOffset }
+        );
+      }
+      if (state.decodeName && !fileHead.hasSpecialName) {
+        fileHead.name = state.decodeName(fileHead.nameBytes);
+      }
+      if (
+        !Number.isSafeInteger(fileHead.size) ||
+        !Number.isSafeInteger(fileHead.unpackedSize)
+      ) {
+        throw new RarError(
+          "INVALID_HEADER",
+          `Invalid file header in ${rarFile.name} at offset ${fileOffset}: sizes above 2^53 bytes are not supported`,
+          { ...context, offset: fileOffset, entry: fileHead.name }
+        );
+      }
+      checkLimits(
+        fileHead,
+        { ...context, offset: fileOffset },
+        opts.limits ?? {},
+        state.countFiles
+      );
+      debug(
+        "volume %d: file header %s at %d, %d data bytes%s",
+        volumeIndex,
+        fileHead.name,
+        fileOffset,
+        fileHead.size,
+        fileHead.continuesFromPrevious ? ", continued" : ""
+      );
+      if (isFirstFileHeader && volumeIndex > 0 && !state.afterGap) {
+        this.checkContinuation(
+          fileHead,
+          state.continuing,
+          rarFile,
+          { ...context, offset: fileOffset },
+          opts
+        );
+      }
+      if (
+        isFirstFileHeader &&
+        state.afterGap &&
+        fileHead.continuesFromPrevious &&
+        fileHead.name !== state.continuing
+      ) {
+        this.diagnose(
+          rarFile,
+          fileOffset,
+          `${fileHead.name}: starts in a missing volume, skipped`
+        );
+      }
+      isFirstFileHeader = false;
+      if (state.isDone && !fileHead.continuesFromPrevious) {
+        break;
+      }
+      owner = fileHead.name;
+      const headerOffset = fileOffset;
       fileOffset += fileHead.headSize;
       function getFileChunk() {
-          if (fileHead.method !== 0x30) {
-              throw new Error("Decompression is not implemented");
+          const errorContext = {
+            ...context,
+            offset: headerOffset,
+            entry: fileHead.name,
+          };
+          const { method, version } = fileHead;
+          if (method !== 0x30 && version > MAX_UNPACK_VERSION) {
+            const needed = (version / 10).toFixed(1);
+            throw new RarError(
+              "UNSUPPORTED_FEATURE",
+              `${fileHead.name} needs RAR ${needed} to extract`,
+              { ...errorContext, feature: `unpack version ${version}` }
+            );
+          }
+          if (method !== 0x30) {
+              throw new RarError(
+                "UNSUPPORTED_METHOD",
+                "Decompression is not implemented",
+                errorContext
+              );
+          }
+          if (fileHead.isEncrypted) {
+              throw new RarError(
+                "ENCRYPTED_ENTRY",
+                "Decryption is not implemented",
+                errorContext
+              );
           }
           return {
               name: fileHead.name,
@@ -70,77 +463,501 @@ export class RarFilesPackage extends EventEmitter {
               chunk: new RarFileChunk(
                 rarFile,
                 fileOffset,
-                fileOffset + fileHead.size - 1
+                fileOffset + fileHead.size - 1,
+                volumeIndex
               ),
           };
       }
-      if (opts.filter) {
-          if (opts.filter(fileHead.name, countFiles)) {
-              fileChunks.push(getFileChunk());
-              retrievedFiles++;
-              if (opts.hasOwnProperty('maxFiles') && retrievedFiles === opts.maxFiles) {
-                  break;
+      const selectFileChunk = () => {
+          try {
+              return getFileChunk();
+          } catch (error) {
+              if (this.entryErrors) {
+                  this.entryErrors.push({
+                    volumeIndex,
+                    offset: headerOffset,
+                    result: {
+                      name: fileHead.name,
+                      ok: false,
+                      error: error as Error,
+                    },
+                  });
+                  return undefined;
+              }
+              if (!opts.tolerant) {
+                  throw error;
               }
+              this.diagnose(
+                rarFile,
+                headerOffset,
+                `${fileHead.name}: ${(error as Error).message}`
+              );
+              return undefined;
+          }
+      };
+      if (fileHead.continuesFromPrevious) {
+          const fileChunk =
+            state.selected.has(fileHead.name) && selectFileChunk();
+          if (fileChunk) {
+              fileChunks.push(fileChunk);
+          }
+      } else if (
+        !opts.filter ||
+        opts.filter(
+          fileHead.name,
+          state.countFiles,
+          describeCompression(fileHead)
+        )
+      ) {
+          const fileChunk = selectFileChunk();
+          if (fileChunk) {
+              fileChunks.push(fileChunk);
+              state.selected.add(fileHead.name);
+              state.isDone = state.selected.size === opts.maxFiles;
           }
       } else {
-          fileChunks.push(getFileChunk());
+          debug("volume %d: filter skipped %s", volumeIndex, fileHead.name);
+      }
+      if (!fileHead.continuesFromPrevious) {
+          state.countFiles++;
       }
+      state.continuing = fileHead.continuesInNext ? fileHead.name : undefined;
       fileOffset += fileHead.size;
-      countFiles++;
+    }
+    if (archiveHeader.hasVolumeAttributes && fileOffset < rarFile.length) {
+      const terminatorHead = await parseHeader(
+        TerminatorHeaderParser,
+        rarFile,
+        fileOffset
+      );
+      if (
+        terminatorHead.type === 0x7b &&
+        terminatorHead.volumeNumber !== undefined &&
+        terminatorHead.volumeNumber !== volumeIndex + state.missingVolumes
+      ) {
+        throw new RarError(
+          "VOLUME_ORDER",
+          `Volume ${rarFile.name} is out of order: expected volume ${
+            volumeIndex + state.missingVolumes
+          }, found volume ${terminatorHead.volumeNumber}`,
+          { ...context, offset: fileOffset }
+        );
+      }
     }
     this.emit("file-parsed", rarFile);
     return fileChunks;
   }
-  async parse(opts: FindOpts): Promise<InnerFile[]> {
-    opts = opts || {};
+  // Only stored comments can be read, compressed ones are listed in the
+  // diagnostics. Archive comments come before the first file header.
+  private async addComment(
+    rarFile: IFileMedia,
+    offset: number,
+    state: ParseState,
+    owner?: string,
+    serviceHead?: IFileHeader
+  ) {
+    let method: number;
+    let start: number;
+    let end: number;
+    if (serviceHead) {
+      method = serviceHead.isEncrypted ? -1 : serviceHead.method;
+      start = offset + serviceHead.headSize;
+      end = start + serviceHead.size - 1;
+    } else {
+      const header = await readRange(
+        rarFile,
+        offset,
+        offset + COMMENT_HEADER_SIZE - 1
+      );
+      if (header[2] !== COMMENT_HEADER_TYPE) {
+        return;
+      }
+      method = header.readUInt8(10);
+      start = offset + COMMENT_HEADER_SIZE;
+      end = offset + header.readUInt16LE(5) - 1;
+    }
+    if (method !== STORE_METHOD) {
+      this.diagnose(
+        rarFile,
+        offset,
+        `${owner ?? "archive"}: comment is packed or encrypted, not available`
+      );
+      return;
+    }
+    const comment =
+      end < start
+        ? ""
+        : (await readRange(rarFile, start, end)).toString("utf-8");
+    if (owner !== undefined) {
+      state.comments.set(owner, comment);
+    } else if (this.archiveInfo && !this.archiveInfo.comment) {
+      this.archiveInfo.comment = comment;
+    }
+  }
+  private async serviceEntry(
+    fileHead: IFileHeader,
+    rarFile: IFileMedia,
+    volumeIndex: number,
+    offset: number,
+    owner?: string
+  ) {
+    const entry: IServiceEntry = {
+      type: fileHead.name,
+      volume: rarFile.name,
+      volumeIndex,
+      offset,
+      packedSize: fileHead.size,
+      unpackedSize: fileHead.unpackedSize,
+      method: fileHead.method,
+      ...(owner !== undefined && { owner }),
+    };
+    if (fileHead.name === STREAM_SERVICE_NAME) {
+      // the stream name, like ":name", follows the name in the header
+      const start =
+        FILE_HEADER_FIXED_SIZE +
+        (fileHead.hasHighSize ? 8 : 0) +
+        fileHead.nameSize;
+      const end = fileHead.headSize - (fileHead.hasSalt ? 8 : 0);
+      if (end > start) {
+        const subData = await readRange(
+          rarFile,
+          offset + start,
+          offset + end - 1
+        );
+        entry.streamName = subData
+          .toString("utf-8")
+          .replace(/^:/, "")
+          .replace(/:\$DATA$/, "");
+      }
+    }
+    if (fileHead.method === 0x30 && !fileHead.isEncrypted && fileHead.size) {
+      const dataOffset = offset + fileHead.headSize;
+      entry.file = new InnerFile(
+        fileHead.name,
+        [
+          new RarFileChunk(
+            rarFile,
+            dataOffset,
+            dataOffset + fileHead.size - 1,
+            volumeIndex
+          ),
+        ],
+        fileHead
+      );
+    }
+    return entry;
+  }
+  private checkContinuation(
+    fileHead: IFileHeader,
+    continuing: string | undefined,
+    rarFile: IFileMedia,
+    context: { volume: string; volumeIndex: number; offset: number },
+    opts: FindOpts
+  ) {
+    const found = fileHead.continuesFromPrevious ? fileHead.name : undefined;
+    if (found === continuing) {
+      return;
+    }
+    const describe = (name: string | undefined) =>
+      name === undefined ? "a new entry" : `the continuation of ${name}`;
+    const error = new RarError(
+      "VOLUME_MISMATCH",
+      `Volume ${rarFile.name} does not belong to this archive: expected ${describe(continuing)}, found ${describe(found)}`,
+      { ...context, entry: fileHead.name }
+    );
+    if (!opts.tolerant) {
+      throw error;
+    }
+    this.diagnose(rarFile, context.offset, error.message);
+  }
+  // Calls the visitor for every block of every volume, without building the
+  // file list. Only opts.maxScanBytes is used.
+  async visitHeaders(visitor: IHeaderVisitor, opts: FindOpts = {}) {
+    const maxScanBytes = opts.maxScanBytes ?? MAX_SFX_STUB_SIZE;
+    await this.confirmVolumes(maxScanBytes);
+    return visitHeaders(this.rarFileBundle.files, visitor, maxScanBytes);
+  }
+  // Sizes and counts of the entries, by compression method and with the
+  // largest ones, read from the headers without building the file list.
+  async stats(opts: FindOpts = {}, largest = 10) {
+    const maxScanBytes = opts.maxScanBytes ?? MAX_SFX_STUB_SIZE;
+    await this.confirmVolumes(maxScanBytes);
+    return archiveStats(this.rarFileBundle.files, maxScanBytes, largest);
+  }
+  // Takes in the .exe files given without other volumes of their set once a
+  // RAR signature is found in them. Without any other volume, a missing
+  // signature is an error like for any first volume. Volumes of different
+  // sets cannot be parsed together.
+  private async confirmVolumes(maxScanBytes: number) {
+    const bundle = this.rarFileBundle;
+    for (const candidate of bundle.sfxCandidates) {
+      if (await findSignature(candidate, maxScanBytes)) {
+        bundle.acceptSfxCandidate(candidate);
+      } else {
+        this.ignoredVolumes.push(candidate);
+      }
+    }
+    bundle.sfxCandidates = [];
+    const [ignored] = this.ignoredVolumes;
+    if (ignored && !bundle.length) {
+      throw new RarError(
+        "NO_SIGNATURE",
+        `No RAR signature found in the first ${maxScanBytes} bytes of ${ignored.name}`,
+        { volume: ignored.name, volumeIndex: 0 }
+      );
+    }
+    const [first, ...rest] = bundle.files;
+    const setName = first && volumeSetName(first.name);
+    const other = rest.find((file) => volumeSetName(file.name) !== setName);
+    if (first && other) {
+      throw new RarError(
+        "VOLUME_MISMATCH",
+        `${first.name} and ${other.name} belong to different volume sets`,
+        { volume: other.name, volumeIndex: bundle.files.indexOf(other) }
+      );
+    }
+  }
+  static scanForSignature(fileMedia: IFileMedia, maxScanBytes = 1024 * 1024) {
+    return findSignature(fileMedia, maxScanBytes);
+  }
+  private diagnose(rarFile: IFileMedia, offset: number, message: string) {
+    debug("%s at %d: %s", rarFile.name, offset, message);
+    this.diagnostics.push({ volume: rarFile.name, offset, message });
+  }
+  async parse(opts: FindOpts = {}): Promise<InnerFile[]> {
+    const innerFiles: InnerFile[] = [];
+    for await (const innerFile of this.parseIter(opts)) {
+      innerFiles.push(innerFile);
+    }
+    this.emit("parsing-complete", innerFiles);
+    return innerFiles;
+  }
+  // Yields each inner file as soon as its last chunk has been found.
+  async *parseIter(opts: FindOpts = {}): AsyncGenerator<InnerFile> {
+    const { metrics } = opts;
+    if (!metrics) {
+      yield* this.parseVolumes(opts);
+      return;
+    }
+    const started = performance.now();
+    try {
+      yield* this.parseVolumes(opts);
+    } catch (error) {
+      metrics.addError(error);
+      throw error;
+    } finally {
+      metrics.addParse(performance.now() - started);
+    }
+  }
+  private async *parseVolumes(opts: FindOpts): AsyncGenerator<InnerFile> {
+    const decodeName = opts.nameEncoding
+      ? nameDecoder(opts.nameEncoding)
+      : undefined;
+    this.diagnostics = [];
+    this.serviceEntries = [];
+    await this.confirmVolumes(opts.maxScanBytes ?? MAX_SFX_STUB_SIZE);
+    for (const duplicate of this.rarFileBundle.duplicates) {
+      this.diagnose(duplicate, 0, "duplicate volume, ignored");
+    }
+    for (const shorter of this.rarFileBundle.shorterCopies) {
+      this.diagnose(shorter, 0, "shorter copy of a volume, ignored");
+    }
+    for (const ignored of this.ignoredVolumes) {
+      this.diagnose(ignored, 0, "no RAR signature found, ignored");
+    }
     this.emit("parsing-start", this.rarFileBundle);
-    const parsedFileChunks: ParsedFileChunkMapping[][] = [];
+    const pending = new Map<string, ParsedFileChunkMapping[]>();
+    const state: ParseState = {
+      countFiles: 0,
+      selected: new Set(),
+      isDone: false,
+      missingVolumes: 0,
+      afterGap: false,
+      comments: new Map(),
+      decodeName,
+    };
     const { files } = this.rarFileBundle;
+    let bytesRead = 0;
+    const reportVolume = (file: IFileMedia, volumeIndex: number) => {
+      bytesRead += file.length;
+      opts.progress?.onProgress({
+        name: file.name,
+        volumeIndex,
+        bytesRead,
+        bytesWritten: 0,
+      });
+    };
+    const toInnerFile = (
+      name: string,
+      mappings: ParsedFileChunkMapping[],
+      missingVolumes?: string[]
+    ) => {
+      const innerFile = new InnerFile(
+        name,
+        mappings.map((m) => m.chunk),
+        mappings[mappings.length - 1]!.fileHead,
+        opts
+      );
+      if (missingVolumes) {
+        innerFile.incomplete = true;
+        innerFile.missingVolumes = missingVolumes;
+      }
+      const comment = state.comments.get(name);
+      if (comment !== undefined) {
+        innerFile.comment = comment;
+      }
+      return innerFile;
+    };
+    function* collect(mappings: ParsedFileChunkMapping[]) {
+      for (const mapping of mappings) {
+        const chunks = pending.get(mapping.name) ?? [];
+        chunks.push(mapping);
+        pending.set(mapping.name, chunks);
+        if (!mapping.fileHead.continuesInNext) {
+          pending.delete(mapping.name);
+          yield toInnerFile(mapping.name, chunks);
+        }
+      }
+    }
     for (let i = 0; i < files.length; ++i) {
       const file = files[i]!;
+      const missing = this.rarFileBundle.missingBefore(i);
+      state.afterGap = missing.length > 0;
+      if (missing.length) {
+        if (!opts.allowMissingVolumes) {
+          throw new RarError(
+            "MISSING_VOLUME",
+            `Missing volume ${missing.join(", ")} before ${file.name}`,
+            { volume: file.name, volumeIndex: i }
+          );
+        }
+        debug("volume %d: %s missing", i, missing.join(", "));
+        state.missingVolumes += missing.length;
+        // entries running into the gap end with the data read so far
+        for (const [name, mappings] of pending) {
+          state.selected.delete(name);
+          yield toInnerFile(name, mappings, missing);
+        }
+        pending.clear();
+      }
 
-      const chunks = await this.parseFile(file, opts);
+      const chunks = await this.parseFile(file, opts, state, i);
+      reportVolume(file, i);
       if (!chunks.length) {
-          this.emit("parsing-complete", []);
-          return [];
+        if (state.isDone) {
+          break;
+        }
+        continue;
       }
       const { fileHead, chunk } = chunks[chunks.length - 1]!;
       const chunkSize = Math.abs(chunk.endOffset - chunk.startOffset);
       let innerFileSize = fileHead.unpackedSize;
-      parsedFileChunks.push(chunks);
+      yield* collect(chunks);
 
-      if (fileHead.continuesInNext) {
+      // volumes fully covered by one entry are assumed to share the layout
+      // of this one, unless they are to be validated
+      if (fileHead.continuesInNext && !opts.validateVolumes) {
         while (Math.abs(innerFileSize - chunkSize) >= chunkSize) {
-          const nextFile = files[++i]!;
+          const nextFile = files[i + 1];
+          if (!nextFile || this.rarFileBundle.missingBefore(i + 1).length) {
+            break;
+          }
+          ++i;
+          debug(
+            "volume %d: %s continues through %s",
+            i,
+            fileHead.name,
+            nextFile.name
+          );
 
-          parsedFileChunks.push([
+          yield* collect([
             {
               name: fileHead.name,
               chunk: new RarFileChunk(
                 nextFile,
                 chunk.startOffset,
-                chunk.endOffset
+                chunk.endOffset,
+                i
               ),
+              fileHead,
             },
           ]);
           this.emit("file-parsed", nextFile);
+          reportVolume(nextFile, i);
           innerFileSize -= chunkSize;
         }
+      } else if (state.isDone) {
+        break;
       }
     }
 
-    const fileChunks = parsedFileChunks.flat();
-
-    const grouped = mapValues(
-      groupBy(fileChunks, (f) => f.name),
-      (value) => value.map((v) => v.chunk)
-    );
-
-    const innerFiles = Object.entries(grouped).map(
-      ([name, chunks]) => new InnerFile(name, chunks)
-    );
-
-    this.emit("parsing-complete", innerFiles);
-    return innerFiles;
+    // entries whose remaining volumes are missing
+    for (const [name, mappings] of pending) {
+      const next = this.rarFileBundle.nextVolumeName;
+      yield toInnerFile(name, mappings, next === undefined ? [] : [next]);
+    }
+  }
+  // A JSON serializable snapshot of the parse, see loadIndex.
+  async exportIndex(opts: FindOpts = {}) {
+    const innerFiles = await this.parse(opts);
+    return createIndex(this.rarFileBundle.files, innerFiles);
+  }
+  // Rebuilds the inner files of an exported index without reading any headers.
+  // Self-extracting volumes are taken from the index instead of scanned for.
+  loadIndex(index: IArchiveIndex, opts: FindOpts = {}) {
+    const bundle = this.rarFileBundle;
+    for (const candidate of bundle.sfxCandidates) {
+      if (index.volumes.some(({ name }) => name === candidate.name)) {
+        bundle.acceptSfxCandidate(candidate);
+      }
+    }
+    return restoreIndex(index, bundle.files, opts);
+  }
+  // Reads every entry and checks its CRC32, without writing anything.
+  // Entries that cannot be read, e.g. compressed ones, are reported like
+  // the others, in archive order.
+  async test(opts: FindOpts = {}) {
+    const entryErrors: IPositionedResult[] = [];
+    this.entryErrors = entryErrors;
+    let innerFiles: InnerFile[];
+    try {
+      innerFiles = await this.parse({ ...opts, validateVolumes: true });
+    } finally {
+      this.entryErrors = undefined;
+    }
+    const results: IPositionedResult[] = [...entryErrors];
+    for (const innerFile of innerFiles) {
+      const { chunk } = innerFile.chunkMap[0] ?? {};
+      const position = {
+        volumeIndex: chunk?.volumeIndex ?? 0,
+        offset: chunk?.startOffset ?? 0,
+      };
+      try {
+        await innerFile.verify();
+        results.push({
+          ...position,
+          result: { name: innerFile.name, ok: true },
+        });
+      } catch (error) {
+        results.push({
+          ...position,
+          result: { name: innerFile.name, ok: false, error: error as Error },
+        });
+      }
+    }
+    return results
+      .sort((a, b) => a.volumeIndex - b.volumeIndex || a.offset - b.offset)
+      .map(({ result }) => result);
+  }
+  async tree(opts: FindOpts = {}) {
+    return buildTree(await this.parse(opts));
+  }
+  async extractAll(destination: string, opts: IExtractOpts = {}) {
+    if (!opts.includeStreams) {
+      return extractFiles(await this.parse(opts), destination, opts);
+    }
+    const innerFiles = await this.parse({ ...opts, includeServiceEntries: true });
+    const streams = this.serviceEntries.filter((e) => e.streamName !== undefined);
+    return extractFiles(innerFiles, destination, opts, streams);
   }
 }
@@ -1,6 +1,9 @@
 //
 import { expect, test } from "vitest";
+import { Readable } from "stream";
 import { InnerFileStream } from "./inner-file-stream.js";
+import { IFileMedia } from "./interfaces.js";
+import { MemoryFileMedia } from "./memory-file-media.js";
 import { RarFileChunk } from "./rar-file-chunk.js";
 
 import { MockFileMedia } from "./parsing/__mocks__/mock-file-media.js";
@@ -44,3 +47,66 @@ test("inner file stream should stream over longer list of file chunks", async ()
   const buffer = await streamToBuffer(rarStream);
   expect(buffer).toEqual(Buffer.from(bufferString, "hex"));
 });
+
+const streamMedia = (stream: () => Promise<Readable>): IFileMedia => ({
+  name: "stream",
+  length: 10,
+  createReadStream: stream,
+});
+
+test("inner file stream should forward errors from opening a chunk", async () => {
+  const fileMedia = new MockFileMedia("123456789ABC");
+  const rarStream = new InnerFileStream([
+    new RarFileChunk(fileMedia, 0, 2),
+    new RarFileChunk(
+      streamMedia(() => Promise.reject(new Error("volume gone"))),
+      0,
+      2
+    ),
+  ]);
+  await expect(streamToBuffer(rarStream)).rejects.toThrow("volume gone");
+});
+
+test("inner file stream should forward errors from a chunk stream", async () => {
+  const source = new Readable({
+    read() {
+      this.destroy(new Error("read failed"));
+    },
+  });
+  const rarStream = new InnerFileStream([
+    new RarFileChunk(streamMedia(() => Promise.resolve(source)), 0, 2),
+  ]);
+  await expect(streamToBuffer(rarStream)).rejects.toThrow("read failed");
+});
+
+test("inner file stream should pause the chunk stream when the consumer is slow", async () => {
+  let produced = 0;
+  const source = new Readable({
+    read() {
+      produced += 1024;
+      this.push(produced > 1024 * 1024 ? null : Buffer.alloc(1024));
+    },
+  });
+  const rarStream = new InnerFileStream(
+    [new RarFileChunk(streamMedia(() => Promise.resolve(source)), 0, 2)],
+    { highWaterMark: 16 }
+  );
+  rarStream.read(0);
+  await new Promise((resolve) => setTimeout(resolve, 10));
+  expect(source.isPaused()).toBe(true);
+  expect(produced).toBeLessThan(64 * 1024);
+});
+
+test("inner file stream should destroy the chunk stream when destroyed", async () => {
+  const fileMedia = new MemoryFileMedia(Buffer.alloc(1024), "small");
+  const rarStream = new InnerFileStream(
+    [new RarFileChunk(fileMedia, 0, 1023)],
+    { highWaterMark: 16 }
+  );
+  rarStream.read(0);
+  await new Promise((resolve) => setTimeout(resolve, 10));
+  const source = rarStream.stream as Readable;
+  rarStream.destroy();
+  await new Promise((resolve) => setTimeout(resolve, 0));
+  expect(source.destroyed).toBe(true);
+});
@@ -0,0 +1,132 @@
+import { expect, test } from "vitest";
+import fs from "fs";
+import os from "os";
+import path from "path";
+import { MemoryFileMedia } from "./memory-file-media.js";
+import {
+  createRarStream,
+  IRarWriterEntry,
+  rarChunks,
+  writeRar,
+} from "./rar-writer.js";
+import { streamToBuffer } from "./stream-utils.js";
+import { crc32 } from "./utils.js";
+
+const readVint = (buffer: Buffer, offset: number) => {
+  let value = 0;
+  let shift = 1;
+  let byte;
+  do {
+    byte = buffer[offset++]!;
+    value += (byte & 0x7f) * shift;
+    shift *= 0x80;
+  } while (byte & 0x80);
+  return { value, offset };
+};
+
+// Splits a RAR 5 volume into its blocks, checking each header CRC.
+const readBlocks = (volume: Buffer) => {
+  expect(volume.subarray(0, 8).toString("hex")).toBe("526172211a070100");
+  const blocks = [];
+  let offset = 8;
+  while (offset < volume.length) {
+    const size = readVint(volume, offset + 4);
+    const end = size.offset + size.value;
+    expect(crc32(volume.subarray(offset + 4, end))).toBe(
+      volume.readUInt32LE(offset)
+    );
+    const type = readVint(volume, size.offset);
+    const flags = readVint(volume, type.offset);
+    const dataSize =
+      flags.value & 0x0002 ? readVint(volume, flags.offset).value : 0;
+    blocks.push({
+      type: type.value,
+      flags: flags.value,
+      data: volume.subarray(end, end + dataSize),
+    });
+    offset = end + dataSize;
+  }
+  return blocks;
+};
+
+const entries = (): IRarWriterEntry[] => [
+  new MemoryFileMedia(Buffer.from("hello"), "a.txt"),
+  {
+    name: "dir",
+    length: 0,
+    isDirectory: true,
+    createReadStream: () => {
+      throw new Error("directories have no data");
+    },
+  },
+  new MemoryFileMedia(Buffer.alloc(3000, "x"), "dir/b.txt"),
+];
+
+const volumes = async (opts = {}) => {
+  const chunks: Buffer[][] = [];
+  for await (const { volumeIndex, data } of rarChunks(entries(), opts)) {
+    (chunks[volumeIndex] ??= []).push(data);
+  }
+  return chunks.map((chunks) => Buffer.concat(chunks));
+};
+
+test("createRarStream writes a main header, file headers and an end header", async () => {
+  const archive = await streamToBuffer(createRarStream(entries()));
+  const blocks = readBlocks(archive);
+  expect(blocks.map((b) => b.type)).toEqual([1, 2, 2, 2, 5]);
+  expect(blocks[1]!.data.toString()).toBe("hello");
+  expect(blocks[2]!.data.length).toBe(0);
+  expect(blocks[3]!.data).toEqual(Buffer.alloc(3000, "x"));
+});
+
+test("rarChunks splits entries across volumes of the given size", async () => {
+  const split = await volumes({ volumeSize: 1024 });
+  expect(split.length).toBe(4);
+  for (const volume of split) {
+    expect(volume.length).toBeLessThan(1025);
+  }
+  const blocks = split.map(readBlocks);
+  // data continues in the next volume, then continues from the previous one
+  expect(blocks[0]!.map((b) => [b.type, b.flags])).toEqual([
+    [1, 0],
+    [2, 0x02],
+    [2, 0x02],
+    [2, 0x12],
+    [5, 0],
+  ]);
+  expect(blocks[3]!.map((b) => [b.type, b.flags])).toEqual([
+    [1, 0],
+    [2, 0x0a],
+    [5, 0],
+  ]);
+  const data = Buffer.concat(
+    blocks.flatMap((volume) =>
+      volume.filter((b) => b.type === 2).map((b) => b.data)
+    )
+  );
+  expect(data).toEqual(
+    Buffer.concat([Buffer.from("hello"), Buffer.alloc(3000, "x")])
+  );
+});
+
+test("rarChunks rejects volumes too small for a header", async () => {
+  await expect(volumes({ volumeSize: 40 })).rejects.toThrow(
+    "Volume size 40 is too small for the header of a.txt"
+  );
+});
+
+test("writeRar names volumes after the destination", async () => {
+  const dir = fs.mkdtempSync(path.join(os.tmpdir(), "rar-stream-"));
+  const destination = path.join(dir, "out.rar");
+  expect(await writeRar(destination, entries())).toEqual([destination]);
+  const paths = await writeRar(destination, entries(), { volumeSize: 1024 });
+  expect(paths.map((p) => path.basename(p))).toEqual([
+    "out.part1.rar",
+    "out.part2.rar",
+    "out.part3.rar",
+    "out.part4.rar",
+  ]);
+  expect(Buffer.concat(paths.map((p) => fs.readFileSync(p)))).toEqual(
+    Buffer.concat(await volumes({ volumeSize: 1024 }))
+  );
+});
@@ -0,0 +1,261 @@
+import { FileHandle, open } from "fs/promises";
+import { Readable } from "stream";
+import { IFileMedia } from "./interfaces.js";
+import { crc32 } from "./utils.js";
+
+const RAR5_SIGNATURE = Buffer.from("526172211a070100", "hex");
+
+const MAIN_HEADER_TYPE = 1;
+const FILE_HEADER_TYPE = 2;
+const END_HEADER_TYPE = 5;
+
+const HEADER_FLAG_DATA = 0x0002;
+const HEADER_FLAG_SPLIT_BEFORE = 0x0008;
+const HEADER_FLAG_SPLIT_AFTER = 0x0010;
+
+const ARCHIVE_FLAG_VOLUME = 0x0001;
+const ARCHIVE_FLAG_VOLUME_NUMBER = 0x0002;
+
+const FILE_FLAG_DIRECTORY = 0x0001;
+const FILE_FLAG_TIME = 0x0002;
+const FILE_FLAG_CRC = 0x0004;
+
+const END_FLAG_MORE_VOLUMES = 0x0001;
+
+const HOST_UNIX = 1;
+const S_IFREG = 0o100000;
+const S_IFDIR = 0o040000;
+
+export interface IRarWriterEntry extends IFileMedia {
+  isDirectory?: boolean;
+  modifiedAt?: Date | undefined;
+  // unix permission bits, defaults to 0644 for files and 0755 for directories
+  mode?: number;
+  // the CRC32 of the data, if known, saves reading it twice
+  crc32?: number | undefined;
+}
+
+export interface IRarWriterOpts {
+  // split into volumes of at most this many bytes
+  volumeSize?: number;
+}
+
+export interface IRarChunk {
+  volumeIndex: number;
+  data: Buffer;
+}
+
+// RAR 5 numbers are stored 7 bits per byte, lowest first, with the high
+// bit set on every byte but the last.
+const vint = (value: number) => {
+  const bytes: number[] = [];
+  do {
+    const low = value % 0x80;
+    value = Math.floor(value / 0x80);
+    bytes.push(value > 0 ? low | 0x80 : low);
+  } while (value > 0);
+  return Buffer.from(bytes);
+};
+
+const uint32 = (value: number) => {
+  const buffer = Buffer.alloc(4);
+  buffer.writeUInt32LE(value >>> 0);
+  return buffer;
+};
+
+// Prefixes the header fields with their size and the CRC32 of both.
+const block = (fields: Buffer[]) => {
+  const body = Buffer.concat(fields);
+  const sized = Buffer.concat([vint(body.length), body]);
+  return Buffer.concat([uint32(crc32(sized)), sized]);
+};
+
+const mainHeader = (volumeIndex: number, isVolume: boolean) =>
+  block([
+    vint(MAIN_HEADER_TYPE),
+    vint(0),
+    vint(
+      (isVolume ? ARCHIVE_FLAG_VOLUME : 0) |
+        (volumeIndex > 0 ? ARCHIVE_FLAG_VOLUME_NUMBER : 0)
+    ),
+    ...(volumeIndex > 0 ? [vint(volumeIndex)] : []),
+  ]);
+
+const endHeader = (hasNextVolume: boolean) =>
+  block([
+    vint(END_HEADER_TYPE),
+    vint(0),
+    vint(hasNextVolume ? END_FLAG_MORE_VOLUMES : 0),
+  ]);
+
+interface IFilePart {
+  size: number;
+  crc: number;
+  splitBefore: boolean;
+  splitAfter: boolean;
+}
+
+const fileHeader = (entry: IRarWriterEntry, part: IFilePart) => {
+  const name = Buffer.from(entry.name.replace(/\\/g, "/"), "utf-8");
+  const modifiedAt = entry.modifiedAt;
+  // the data size is present even for directories and empty files
+  const headerFlags =
+    HEADER_FLAG_DATA |
+    (part.splitBefore ? HEADER_FLAG_SPLIT_BEFORE : 0) |
+    (part.splitAfter ? HEADER_FLAG_SPLIT_AFTER : 0);
+  const fileFlags =
+    (entry.isDirectory ? FILE_FLAG_DIRECTORY : FILE_FLAG_CRC) |
+    (modifiedAt ? FILE_FLAG_TIME : 0);
+  const mode = entry.mode ?? (entry.isDirectory ? 0o755 : 0o644);
+  return block([
+    vint(FILE_HEADER_TYPE),
+    vint(headerFlags),
+    vint(part.size),
+    vint(fileFlags),
+    vint(entry.isDirectory ? 0 : entry.length),
+    vint((entry.isDirectory ? S_IFDIR : S_IFREG) | (mode & 0o7777)),
+    ...(modifiedAt ? [uint32(Math.floor(modifiedAt.getTime() / 1000))] : []),
+    ...(entry.isDirectory ? [] : [uint32(part.crc)]),
+    // version 0, store method
+    vint(0),
+    vint(HOST_UNIX),
+    vint(name.length),
+    name,
+  ]);
+};
+
+async function* readRange(media: IFileMedia, start: number, end: number) {
+  if (end < start) {
+    return;
+  }
+  for await (const data of await media.createReadStream({ start, end })) {
+    yield data as Buffer;
+  }
+}
+
+const crcOfRange = async (media: IFileMedia, start: number, end: number) => {
+  let crc = 0;
+  for await (const data of readRange(media, start, end)) {
+    crc = crc32(data, crc);
+  }
+  return crc;
+};
+
+// Writes the entries into a RAR 5 archive without compression. Entries are
+// read twice, once for their CRC32 and once for their data, unless they carry
+// a CRC32, so nothing is buffered beyond the reads themselves.
+export async function* rarChunks(
+  entries: IRarWriterEntry[],
+  opts: IRarWriterOpts = {}
+): AsyncGenerator<IRarChunk> {
+  const { volumeSize } = opts;
+  const isVolume = volumeSize !== undefined;
+  const endSize = endHeader(true).length;
+  let volumeIndex = 0;
+  let used = 0;
+  let hasEntries = false;
+  const write = (data: Buffer) => {
+    used += data.length;
+    return { volumeIndex, data };
+  };
+  const startVolume = () => {
+    used = 0;
+    hasEntries = false;
+    return [
+      write(RAR5_SIGNATURE),
+      write(mainHeader(volumeIndex, isVolume)),
+    ];
+  };
+  yield* startVolume();
+  for (const entry of entries) {
+    const length = entry.isDirectory ? 0 : entry.length;
+    const fileCrc = entry.crc32 ?? (await crcOfRange(entry, 0, length - 1));
+    let offset = 0;
+    for (;;) {
+      const remaining = length - offset;
+      let size = remaining;
+      if (volumeSize !== undefined) {
+        // the header for all remaining data is at least as long as the real one
+        const headerSize = fileHeader(entry, {
+          size: remaining,
+          crc: 0,
+          splitBefore: offset > 0,
+          splitAfter: true,
+        }).length;
+        const available = volumeSize - used - headerSize - endSize;
+        if (available < Math.min(remaining, 1)) {
+          if (!hasEntries) {
+            throw new Error(
+              `Volume size ${volumeSize} is too small for the header of ${entry.name}`
+            );
+          }
+          yield write(endHeader(true));
+          volumeIndex++;
+          yield* startVolume();
+          continue;
+        }
+        size = Math.min(remaining, available);
+      }
+      const splitAfter = offset + size < length;
+      yield write(
+        fileHeader(entry, {
+          size,
+          // split parts but the last hold the CRC32 of their own data
+          crc: splitAfter
+            ? await crcOfRange(entry, offset, offset + size - 1)
+            : fileCrc,
+          splitBefore: offset > 0,
+          splitAfter,
+        })
+      );
+      hasEntries = true;
+      for await (const data of readRange(entry, offset, offset + size - 1)) {
+        yield write(data);
+      }
+      offset += size;
+      if (offset >= length) {
+        break;
+      }
+    }
+  }
+  yield write(endHeader(false));
+}
+
+// A single volume archive as a stream.
+export const createRarStream = (entries: IRarWriterEntry[]) =>
+  Readable.from(
+    (async function* () {
+      for await (const { data } of rarChunks(entries)) {
+        yield data;
+      }
+    })()
+  );
+
+// Writes the archive to destination, or with opts.volumeSize to volumes named
+// like destination with .part1.rar, .part2.rar and so on. Resolves with the
+// paths written.
+export async function writeRar(
+  destination: string,
+  entries: IRarWriterEntry[],
+  opts: IRarWriterOpts = {}
+) {
+  const paths: string[] = [];
+  let file: FileHandle | undefined;
+  try {
+    for await (const { volumeIndex, data } of rarChunks(entries, opts)) {
+      if (paths.length === volumeIndex) {
+        await file?.close();
+        paths.push(
+          opts.volumeSize === undefined
+            ? destination
+            : `${destination.replace(/\.rar$/i, "")}.part${volumeIndex + 1}.rar`
+        );
+        file = await open(paths[volumeIndex]!, "w");
+      }
+      await file!.write(data);
+    }
+  } finally {
+    await file?.close();
+  }
+  return paths;
+}
@@ -0,0 +1,126 @@
+import { expect, test } from "vitest";
+import { MemoryFileMedia } from "./memory-file-media.js";
+import {
+  createTarStream,
+  createZipStream,
+  IRepackageEntry,
+} from "./repackage.js";
+import { streamToBuffer } from "./stream-utils.js";
+import { crc32 } from "./utils.js";
+
+const modifiedAt = new Date(2020, 0, 2, 3, 4, 6);
+
+const entries = (): IRepackageEntry[] => [
+  Object.assign(new MemoryFileMedia(Buffer.from("hello"), "a.txt"), {
+    modifiedAt,
+  }),
+  {
+    name: "dir",
+    length: 0,
+    isDirectory: true,
+    createReadStream: () => {
+      throw new Error("directories have no data");
+    },
+  },
+  new MemoryFileMedia(Buffer.from("nested"), "dir\\b.txt"),
+];
+
+test("createZipStream stores entries with a central directory", async () => {
+  const zip = await streamToBuffer(createZipStream(entries()));
+  expect(zip.readUInt32LE(0)).toBe(0x04034b50);
+  expect(zip.readUInt32LE(14)).toBe(crc32(Buffer.from("hello")));
+  expect(zip.readUInt16LE(10)).toBe((3 << 11) | (4 << 5) | 3);
+  expect(zip.readUInt16LE(12)).toBe((40 << 9) | (1 << 5) | 2);
+  expect(zip.subarray(30, 40).toString()).toBe("a.txthello");
+
+  const end = zip.subarray(zip.length - 22);
+  expect(end.readUInt32LE(0)).toBe(0x06054b50);
+  expect(end.readUInt16LE(10)).toBe(3);
+  const names = [];
+  let offset = end.readUInt32LE(16);
+  for (let i = 0; i < 3; i++) {
+    expect(zip.readUInt32LE(offset)).toBe(0x02014b50);
+    const nameLength = zip.readUInt16LE(offset + 28);
+    names.push([
+      zip.subarray(offset + 46, offset + 46 + nameLength).toString(),
+      (zip.readUInt32LE(offset + 38) >>> 16).toString(8),
+    ]);
+    offset += 46 + nameLength;
+  }
+  expect(names).toEqual([
+    ["a.txt", "100644"],
+    ["dir/", "40755"],
+    ["dir/b.txt", "100644"],
+  ]);
+});
+
+const tarHeaders = (tar: Buffer) => {
+  const headers = [];
+  for (let offset = 0; tar[offset]; ) {
+    const header = tar.subarray(offset, offset + 512);
+    const field = (start: number, length: number) =>
+      header.toString("utf-8", start, start + length).replace(/\0.*$/s, "");
+    const checksum = header.reduce(
+      (sum, byte, i) => sum + (i >= 148 && i < 156 ? 0x20 : byte),
+      0
+    );
+    expect(parseInt(field(148, 8), 8)).toBe(checksum);
+    const size = parseInt(field(124, 12), 8);
+    headers.push({
+      name: field(0, 100),
+      prefix: field(345, 155),
+      type: field(156, 1),
+      size,
+      data: tar.subarray(offset + 512, offset + 512 + size).toString(),
+    });
+    offset += 512 + Math.ceil(size / 512) * 512;
+  }
+  return headers;
+};
+
+test("createTarStream writes ustar headers", async () => {
+  const tar = await streamToBuffer(createTarStream(entries()));
+  expect(tar.length % 512).toBe(0);
+  expect(tarHeaders(tar)).toEqual([
+    { name: "a.txt", prefix: "", type: "0", size: 5, data: "hello" },
+    { name: "dir/", prefix: "", type: "5", size: 0, data: "" },
+    { name: "dir/b.txt", prefix: "", type: "0", size: 6, data: "nested" },
+  ]);
+});
+
+test("repackaged names cannot leave the directory they are unpacked to", async () => {
+  const named = (name: string) => new MemoryFileMedia(Buffer.from("x"), name);
+  const tar = await streamToBuffer(
+    createTarStream(["/abs/a.txt", "a/../b.txt", "C:\\c.txt"].map(named))
+  );
+  expect(tarHeaders(tar).map((h) => h.name)).toEqual([
+    "abs/a.txt",
+    "b.txt",
+    "c.txt",
+  ]);
+  for (const create of [createTarStream, createZipStream]) {
+    for (const name of ["../x", "a/../../x"]) {
+      await expect(streamToBuffer(create([named(name)]))).rejects.toThrow(
+        `Refusing to extract ${name} outside of the destination`
+      );
+    }
+  }
+});
+
+test("createTarStream keeps long names", async () => {
+  const split = `${"d".repeat(120)}/${"f".repeat(90)}`;
+  const long = "x".repeat(300);
+  const tar = await streamToBuffer(
+    createTarStream([
+      new MemoryFileMedia(Buffer.from("1"), split),
+      new MemoryFileMedia(Buffer.from("2"), long),
+    ])
+  );
+  const [first, pax, second] = tarHeaders(tar);
+  expect(first).toMatchObject({
+    prefix: "d".repeat(120),
+    name: "f".repeat(90),
+  });
+  expect(pax).toMatchObject({ type: "x", data: `310 path=${long}\n` });
+  expect(second).toMatchObject({ type: "0", data: "2" });
+});
@@ -0,0 +1,331 @@
+import { Readable } from "stream";
+import { IFileMedia } from "./interfaces.js";
+import type { IRarWriterEntry } from "./rar-writer.js";
+import { safePath } from "./safe-path.js";
+import { crc32 } from "./utils.js";
+
+// Entries are the same as for the RAR writer, InnerFiles among them.
+export type IRepackageEntry = IRarWriterEntry;
+
+const ZIP_LOCAL_HEADER = 0x04034b50;
+const ZIP_CENTRAL_HEADER = 0x02014b50;
+const ZIP64_END_OF_CENTRAL_DIRECTORY = 0x06064b50;
+const ZIP64_END_LOCATOR = 0x07064b50;
+const ZIP_END_OF_CENTRAL_DIRECTORY = 0x06054b50;
+const ZIP64_EXTRA_FIELD = 0x0001;
+const ZIP_UTF8_FLAG = 0x0800;
+const ZIP_VERSION = 20;
+const ZIP64_VERSION = 45;
+const MADE_BY_UNIX = 3 << 8;
+const MAX_UINT16 = 0xffff;
+const MAX_UINT32 = 0xffffffff;
+
+const TAR_BLOCK_SIZE = 512;
+// 11 octal digits
+const MAX_TAR_SIZE = 0o77777777777;
+
+const S_IFREG = 0o100000;
+const S_IFDIR = 0o040000;
+const FILE_ATTRIBUTE_DIRECTORY = 0x10;
+
+async function* readAll(media: IFileMedia) {
+  if (!media.length) {
+    return;
+  }
+  for await (const data of await media.createReadStream({
+    start: 0,
+    end: media.length - 1,
+  })) {
+    yield data as Buffer;
+  }
+}
+
+const crcOf = async (media: IFileMedia) => {
+  let crc = 0;
+  for await (const data of readAll(media)) {
+    crc = crc32(data, crc);
+  }
+  return crc;
+};
+
+// Names are made relative like for extraction, so the archives written
+// cannot place files outside of where they are unpacked.
+const entryName = (entry: IRepackageEntry) => {
+  const name = safePath(entry.name, false);
+  return entry.isDirectory && !name.endsWith("/") ? `${name}/` : name;
+};
+
+const entryMode = (entry: IRepackageEntry) =>
+  (entry.mode ?? (entry.isDirectory ? 0o755 : 0o644)) & 0o7777;
+
+const dosDateTime = (date = new Date()) => {
+  const year = Math.min(Math.max(date.getFullYear(), 1980), 2107);
+  return {
+    time:
+      (date.getHours() << 11) |
+      (date.getMinutes() << 5) |
+      Math.floor(date.getSeconds() / 2),
+    date: ((year - 1980) << 9) | ((date.getMonth() + 1) << 5) | date.getDate(),
+  };
+};
+
+const uint64 = (value: number) => {
+  const buffer = Buffer.alloc(8);
+  buffer.writeBigUInt64LE(BigInt(value));
+  return buffer;
+};
+
+// Sizes and offsets past 4 GiB move into the zip64 extra field.
+const zip64Extra = (values: number[]) =>
+  values.length
+    ? Buffer.concat([
+        Buffer.from([ZIP64_EXTRA_FIELD, 0, values.length * 8, 0]),
+        ...values.map(uint64),
+      ])
+    : Buffer.alloc(0);
+
+interface IZipRecord {
+  name: Buffer;
+  entry: IRepackageEntry;
+  crc: number;
+  size: number;
+  offset: number;
+}
+
+const localHeader = ({ name, entry, crc, size }: IZipRecord) => {
+  const isZip64 = size >= MAX_UINT32;
+  const extra = zip64Extra(isZip64 ? [size, size] : []);
+  const header = Buffer.alloc(30);
+  const { time, date } = dosDateTime(entry.modifiedAt);
+  header.writeUInt32LE(ZIP_LOCAL_HEADER, 0);
+  header.writeUInt16LE(isZip64 ? ZIP64_VERSION : ZIP_VERSION, 4);
+  header.writeUInt16LE(ZIP_UTF8_FLAG, 6);
+  header.writeUInt16LE(time, 10);
+  header.writeUInt16LE(date, 12);
+  header.writeUInt32LE(crc, 14);
+  header.writeUInt32LE(isZip64 ? MAX_UINT32 : size, 18);
+  header.writeUInt32LE(isZip64 ? MAX_UINT32 : size, 22);
+  header.writeUInt16LE(name.length, 26);
+  header.writeUInt16LE(extra.length, 28);
+  return Buffer.concat([header, name, extra]);
+};
+
+const centralHeader = ({ name, entry, crc, size, offset }: IZipRecord) => {
+  const zip64Values = [
+    ...(size >= MAX_UINT32 ? [size, size] : []),
+    ...(offset >= MAX_UINT32 ? [offset] : []),
+  ];
+  const extra = zip64Extra(zip64Values);
+  const version = zip64Values.length ? ZIP64_VERSION : ZIP_VERSION;
+  const header = Buffer.alloc(46);
+  const { time, date } = dosDateTime(entry.modifiedAt);
+  header.writeUInt32LE(ZIP_CENTRAL_HEADER, 0);
+  header.writeUInt16LE(MADE_BY_UNIX | version, 4);
+  header.writeUInt16LE(version, 6);
+  header.writeUInt16LE(ZIP_UTF8_FLAG, 8);
+  header.writeUInt16LE(time, 12);
+  header.writeUInt16LE(date, 14);
+  header.writeUInt32LE(crc, 16);
+  header.writeUInt32LE(Math.min(size, MAX_UINT32), 20);
+  header.writeUInt32LE(Math.min(size, MAX_UINT32), 24);
+  header.writeUInt16LE(name.length, 28);
+  header.writeUInt16LE(extra.length, 30);
+  const mode = (entry.isDirectory ? S_IFDIR : S_IFREG) | entryMode(entry);
+  header.writeUInt32LE(
+    ((mode << 16) | (entry.isDirectory ? FILE_ATTRIBUTE_DIRECTORY : 0)) >>> 0,
+    38
+  );
+  header.writeUInt32LE(Math.min(offset, MAX_UINT32), 42);
+  return Buffer.concat([header, name, extra]);
+};
+
+const endOfCentralDirectory = (count: number, size: number, offset: number) => {
+  const isZip64 =
+    count >= MAX_UINT16 || size >= MAX_UINT32 || offset >= MAX_UINT32;
+  const end = Buffer.alloc(22);
+  end.writeUInt32LE(ZIP_END_OF_CENTRAL_DIRECTORY, 0);
+  end.writeUInt16LE(Math.min(count, MAX_UINT16), 8);
+  end.writeUInt16LE(Math.min(count, MAX_UINT16), 10);
+  end.writeUInt32LE(Math.min(size, MAX_UINT32), 12);
+  end.writeUInt32LE(Math.min(offset, MAX_UINT32), 16);
+  if (!isZip64) {
+    return end;
+  }
+  const record = Buffer.alloc(56);
+  record.writeUInt32LE(ZIP64_END_OF_CENTRAL_DIRECTORY, 0);
+  record.writeBigUInt64LE(44n, 4);
+  record.writeUInt16LE(MADE_BY_UNIX | ZIP64_VERSION, 12);
+  record.writeUInt16LE(ZIP64_VERSION, 14);
+  record.writeBigUInt64LE(BigInt(count), 24);
+  record.writeBigUInt64LE(BigInt(count), 32);
+  record.writeBigUInt64LE(BigInt(size), 40);
+  record.writeBigUInt64LE(BigInt(offset), 48);
+  const locator = Buffer.alloc(20);
+  locator.writeUInt32LE(ZIP64_END_LOCATOR, 0);
+  locator.writeBigUInt64LE(BigInt(offset + size), 8);
+  locator.writeUInt32LE(1, 16);
+  return Buffer.concat([record, locator, end]);
+};
+
+// Stores the entries in a ZIP archive without compression. The CRC32 must be
+// known before the data, so entries are read twice unless they carry one,
+// as InnerFiles do.
+export async function* zipChunks(
+  entries: IRepackageEntry[]
+): AsyncGenerator<Buffer> {
+  const records: IZipRecord[] = [];
+  let offset = 0;
+  for (const entry of entries) {
+    const size = entry.isDirectory ? 0 : entry.length;
+    const record: IZipRecord = {
+      name: Buffer.from(entryName(entry), "utf-8"),
+      entry,
+      crc: entry.isDirectory ? 0 : entry.crc32 ?? (await crcOf(entry)),
+      size,
+      offset,
+    };
+    records.push(record);
+    const header = localHeader(record);
+    yield header;
+    if (!entry.isDirectory) {
+      yield* readAll(entry);
+    }
+    offset += header.length + size;
+  }
+  const central = records.map(centralHeader);
+  yield* central;
+  yield endOfCentralDirectory(
+    records.length,
+    central.reduce((total, header) => total + header.length, 0),
+    offset
+  );
+}
+
+const writeString = (
+  block: Buffer,
+  value: string,
+  offset: number,
+  length: number
+) => block.write(value, offset, length, "utf-8");
+
+const writeOctal = (
+  block: Buffer,
+  value: number,
+  offset: number,
+  length: number
+) =>
+  writeString(
+    block,
+    `${value.toString(8).padStart(length - 1, "0")}\0`,
+    offset,
+    length
+  );
+
+// Names up to 100 bytes fit the name field, longer ones up to 255 bytes can be
+// split at a slash into the ustar prefix.
+const splitName = (name: string) => {
+  if (Buffer.byteLength(name) <= 100) {
+    return { prefix: "", name };
+  }
+  for (let i = name.indexOf("/"); i !== -1; i = name.indexOf("/", i + 1)) {
+    const prefix = name.slice(0, i);
+    const rest = name.slice(i + 1);
+    if (Buffer.byteLength(prefix) <= 155 && Buffer.byteLength(rest) <= 100) {
+      return { prefix, name: rest };
+    }
+  }
+  return undefined;
+};
+
+const tarHeader = (
+  name: string,
+  prefix: string,
+  type: string,
+  size: number,
+  mode: number,
+  modifiedAt: Date | undefined
+) => {
+  const block = Buffer.alloc(TAR_BLOCK_SIZE);
+  writeString(block, name, 0, 100);
+  writeOctal(block, mode, 100, 8);
+  writeOctal(block, 0, 108, 8);
+  writeOctal(block, 0, 116, 8);
+  writeOctal(block, size, 124, 12);
+  writeOctal(
+    block,
+    Math.max(0, Math.floor((modifiedAt ?? new Date()).getTime() / 1000)),
+    136,
+    12
+  );
+  block.fill(" ", 148, 156);
+  writeString(block, type, 156, 1);
+  writeString(block, "ustar\0", 257, 6);
+  writeString(block, "00", 263, 2);
+  writeString(block, prefix, 345, 155);
+  let checksum = 0;
+  for (const byte of block) {
+    checksum += byte;
+  }
+  writeString(block, `${checksum.toString(8).padStart(6, "0")}\0 `, 148, 8);
+  return block;
+};
+
+const padding = (size: number) =>
+  Buffer.alloc((TAR_BLOCK_SIZE - (size % TAR_BLOCK_SIZE)) % TAR_BLOCK_SIZE);
+
+// A pax record is prefixed with its own length in decimal, which includes
+// the digits of the length itself.
+const paxRecord = (key: string, value: string) => {
+  const text = ` ${key}=${value}\n`;
+  const length = Buffer.byteLength(text);
+  let total = length + String(length).length;
+  if (String(total).length > String(length).length) {
+    total++;
+  }
+  return `${total}${text}`;
+};
+
+// Writes the entries as a ustar archive. Names or sizes that do not fit the
+// header are stored in a pax extended header before the entry.
+export async function* tarChunks(
+  entries: IRepackageEntry[]
+): AsyncGenerator<Buffer> {
+  for (const entry of entries) {
+    const fullName = entryName(entry);
+    const size = entry.isDirectory ? 0 : entry.length;
+    const split = splitName(fullName);
+    const records = [
+      ...(split ? [] : [paxRecord("path", fullName)]),
+      ...(size > MAX_TAR_SIZE ? [paxRecord("size", String(size))] : []),
+    ];
+    if (records.length) {
+      const pax = Buffer.from(records.join(""), "utf-8");
+      yield tarHeader("PaxHeader", "", "x", pax.length, 0o644, entry.modifiedAt);
+      yield pax;
+      yield padding(pax.length);
+    }
+    const { prefix, name } = split ?? {
+      prefix: "",
+      name: fullName.slice(-100),
+    };
+    yield tarHeader(
+      name,
+      prefix,
+      entry.isDirectory ? "5" : "0",
+      Math.min(size, MAX_TAR_SIZE),
+      entryMode(entry),
+      entry.modifiedAt
+    );
+    if (!entry.isDirectory) {
+      yield* readAll(entry);
+      yield padding(size);
+    }
+  }
+  yield Buffer.alloc(TAR_BLOCK_SIZE * 2);
+}
+
+export const createZipStream = (entries: IRepackageEntry[]) =>
+  Readable.from(zipChunks(entries), { objectMode: false });
+
+export const createTarStream = (entries: IRepackageEntry[]) =>
+  Readable.from(tarChunks(entries), { objectMode: false });
@@ -0,0 +1,28 @@
+import { expect, test } from "vitest";
+import { safePath } from "./safe-path.js";
+
+test("safePath converts windows separators", () => {
+  expect(safePath("docs\\sub\\a.txt", false)).toBe("docs/sub/a.txt");
+  expect(safePath("docs/./sub//a.txt", false)).toBe("docs/sub/a.txt");
+});
+
+test("safePath drops drive prefixes and leading slashes", () => {
+  expect(safePath("C:\\Users\\a.txt", false)).toBe("Users/a.txt");
+  expect(safePath("/etc/passwd", false)).toBe("etc/passwd");
+  expect(safePath("\\\\server\\share\\a.txt", false)).toBe("server/share/a.txt");
+});
+
+test("safePath resolves .. but never above the root", () => {
+  expect(safePath("a/b/../c.txt", false)).toBe("a/c.txt");
+  expect(() => safePath("a/../../c.txt", false)).toThrow(
+    "Refusing to extract a/../../c.txt outside of the destination"
+  );
+  expect(() => safePath("..\\c.txt", false)).toThrow("outside of");
+});
+
+test("safePath replaces names windows cannot store", () => {
+  expect(safePath('what?/a<b>:"c"|*.txt', true)).toBe("what_/a_b___c___.txt");
+  expect(safePath("dir./name. ", true)).toBe("dir_/name__");
+  expect(safePath("con/nul.txt/lpt1", true)).toBe("_con/_nul.txt/_lpt1");
+  expect(safePath("what?/console.txt", false)).toBe("what?/console.txt");
+});
@@ -0,0 +1,38 @@
+const DRIVE_PREFIX = /^[a-z]:$/i;
+const WINDOWS_INVALID_CHARS = /[<>:"|?*\x00-\x1f]/g;
+const WINDOWS_RESERVED_NAME = /^(con|prn|aux|nul|com\d|lpt\d)(\..*)?$/i;
+
+const windowsSafeSegment = (segment: string) => {
+  const safe = segment
+    .replace(WINDOWS_INVALID_CHARS, "_")
+    .replace(/[. ]+$/, (trailing) => "_".repeat(trailing.length));
+  return WINDOWS_RESERVED_NAME.test(safe) ? `_${safe}` : safe;
+};
+
+// Turns an entry name into a relative path with forward slashes. Both
+// separators are accepted, drive prefixes and leading slashes are dropped
+// and ".." may not climb above the root. With windows set, characters and
+// names that Windows cannot store are replaced as well.
+export function safePath(
+  name: string,
+  windows = process.platform === "win32"
+) {
+  const segments: string[] = [];
+  name.split(/[\\/]/).forEach((segment, index) => {
+    const isDrive = index === 0 && DRIVE_PREFIX.test(segment);
+    if (!segment || segment === "." || isDrive) {
+      return;
+    }
+    if (segment === "..") {
+      if (!segments.length) {
+        throw new Error(
+          `Refusing to extract ${name} outside of the destination`
+        );
+      }
+      segments.pop();
+      return;
+    }
+    segments.push(windows ? windowsSafeSegment(segment) : segment);
+  });
+  return segments.join("/");
+}
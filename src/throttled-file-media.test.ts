@@ -0,0 +1,94 @@
+import { afterEach, beforeEach, expect, test, vi } from "vitest";
+
+import { MemoryFileMedia } from "./memory-file-media.js";
+import { Metrics } from "./metrics.js";
+import { mockRarArchive } from "./parsing/__mocks__/mock-rar-archive.js";
+import { RarFilesPackage } from "./rar-files-package.js";
+import { streamToBuffer } from "./stream-utils.js";
+import { RateLimiter, ThrottledFileMedia } from "./throttled-file-media.js";
+
+const data = Buffer.from("0123456789abcdefghij");
+
+beforeEach(() => {
+  vi.useFakeTimers({ toFake: ["setTimeout", "Date"] });
+});
+
+afterEach(() => {
+  vi.useRealTimers();
+});
+
+// Tells whether the promise settles within the given milliseconds.
+const settlesWithin = async (promise: Promise<unknown>, ms: number) => {
+  let settled = false;
+  void promise.then(() => {
+    settled = true;
+  });
+  await vi.advanceTimersByTimeAsync(ms);
+  return settled;
+};
+
+test("RateLimiter lets the burst through, then waits for the rate", async () => {
+  const limiter = new RateLimiter(1000, 100);
+  expect(await settlesWithin(limiter.take(100), 0)).toBe(true);
+  // 50 bytes over the burst at 1000 bytes per second
+  const next = limiter.take(50);
+  expect(await settlesWithin(next, 49)).toBe(false);
+  expect(await settlesWithin(next, 1)).toBe(true);
+});
+
+test("RateLimiter rejects rates that are not positive", () => {
+  expect(() => new RateLimiter(0)).toThrow(
+    "The rate and burst must be positive"
+  );
+});
+
+test("ThrottledFileMedia caps the rate of reads", async () => {
+  const media = new ThrottledFileMedia(new MemoryFileMedia(data, "digits"), {
+    bytesPerSecond: 200,
+    burst: 10,
+  });
+  const read = media
+    .createReadStream({ start: 0, end: 19 })
+    .then((stream) => streamToBuffer(stream));
+  // 10 bytes over the burst at 200 bytes per second
+  expect(await settlesWithin(read, 49)).toBe(false);
+  expect(await settlesWithin(read, 1)).toBe(true);
+  expect(await read).toEqual(data);
+});
+
+test("ThrottledFileMedia forwards prefetching to the wrapped media", async () => {
+  const prefetch = vi.fn(async () => {});
+  const media = new ThrottledFileMedia(
+    Object.assign(new MemoryFileMedia(data, "digits"), { prefetch }),
+    { bytesPerSecond: 200 }
+  );
+  await media.prefetch({ start: 0, end: 9 });
+  expect(prefetch).toHaveBeenCalledWith({ start: 0, end: 9 });
+});
+
+test("Metrics count bytes read per volume and per entry", async () => {
+  const metrics = new Metrics();
+  const archive = mockRarArchive([
+    { name: "a.txt", data: Buffer.from("hello") },
+    { name: "b.txt", data: Buffer.from("world!") },
+  ]);
+  const volume = new ThrottledFileMedia(
+    new MemoryFileMedia(archive, "test.rar"),
+    { metrics }
+  );
+  const [a, b] = await new RarFilesPackage([volume]).parse({ metrics });
+  const parsed = metrics.bytesRead;
+  expect(parsed).toBeGreaterThan(0);
+  await a!.readToEnd();
+  await b!.readToEnd();
+  await b!.readToEnd();
+  expect(metrics.bytesRead).toBe(parsed + 17);
+  expect(metrics.volumes.get("test.rar")).toBe(metrics.bytesRead);
+  expect([...metrics.entries]).toEqual([
+    ["a.txt", 5],
+    ["b.txt", 12],
+  ]);
+  metrics.reset();
+  expect(metrics.bytesRead).toBe(0);
+  expect(metrics.entries.size).toBe(0);
+});
@@ -0,0 +1,80 @@
+import { Readable } from "stream";
+import { IFileMedia, IReadInterval } from "./interfaces.js";
+import { Metrics } from "./metrics.js";
+
+const wait = (ms: number) => new Promise((resolve) => setTimeout(resolve, ms));
+
+// A token bucket holding up to burst bytes, refilled at bytesPerSecond.
+// Share one between volumes to cap their combined rate.
+export class RateLimiter {
+  private tokens: number;
+  private updatedAt = Date.now();
+  private queue = Promise.resolve();
+  constructor(readonly bytesPerSecond: number, readonly burst = bytesPerSecond) {
+    if (!(bytesPerSecond > 0) || !(burst > 0)) {
+      throw new RangeError("The rate and burst must be positive");
+    }
+    this.tokens = burst;
+  }
+  // Resolves once length bytes may pass. Callers are served in order and a
+  // read larger than the burst leaves a debt the next one waits for.
+  take(length: number) {
+    const turn = this.queue.then(() => this.wait(length));
+    this.queue = turn;
+    return turn;
+  }
+  private async wait(length: number) {
+    const now = Date.now();
+    this.tokens = Math.min(
+      this.burst,
+      this.tokens + ((now - this.updatedAt) * this.bytesPerSecond) / 1000
+    );
+    this.updatedAt = now;
+    this.tokens -= length;
+    if (this.tokens < 0) {
+      await wait((-this.tokens / this.bytesPerSecond) * 1000);
+    }
+  }
+}
+
+export interface ThrottledFileMediaOptions {
+  bytesPerSecond?: number;
+  burst?: number;
+  // a limiter shared with other volumes, instead of bytesPerSecond
+  limiter?: RateLimiter;
+  metrics?: Metrics;
+}
+
+export class ThrottledFileMedia implements IFileMedia {
+  name: string;
+  length: number;
+  private limiter: RateLimiter | undefined;
+  constructor(
+    private media: IFileMedia,
+    private opts: ThrottledFileMediaOptions = {}
+  ) {
+    this.name = media.name;
+    this.length = media.length;
+    this.limiter =
+      opts.limiter ??
+      (opts.bytesPerSecond === undefined
+        ? undefined
+        : new RateLimiter(opts.bytesPerSecond, opts.burst));
+  }
+  async createReadStream(interval: IReadInterval) {
+    const stream = await this.media.createReadStream(interval);
+    return Readable.from(this.throttle(stream), { objectMode: false });
+  }
+  // Warming a cache below is not throttled, only the reads handed out are.
+  async prefetch(interval: IReadInterval) {
+    await this.media.prefetch?.(interval);
+  }
+  private async *throttle(stream: NodeJS.ReadableStream) {
+    for await (const data of stream) {
+      const length = (data as Buffer).length;
+      await this.limiter?.take(length);
+      this.opts.metrics?.addVolumeBytes(this.name, length);
+      yield data as Buffer;
+    }
+  }
+}
@@ -0,0 +1,71 @@
+import { expect, test } from "vitest";
+
+import { crc32, dosDateTimeToDate, softwareCrc32 } from "./utils.js";
+
+test("crc32 should match the reference check value", () => {
+  expect(crc32(Buffer.from("123456789"))).toBe(0xcbf43926);
+  expect(crc32(Buffer.alloc(0))).toBe(0);
+});
+
+test("crc32 should continue from a previous crc", () => {
+  const first = crc32(Buffer.from("1234"));
+  expect(crc32(Buffer.from("56789"), first)).toBe(0xcbf43926);
+});
+
+const bitwiseCrc32 = (data: Uint8Array) => {
+  let crc = ~0;
+  for (const byte of data) {
+    crc ^= byte;
+    for (let k = 0; k < 8; k++) {
+      crc = crc & 1 ? 0xedb88320 ^ (crc >>> 1) : crc >>> 1;
+    }
+  }
+  return ~crc >>> 0;
+};
+
+test("softwareCrc32 matches a bitwise crc for every length and offset", () => {
+  const data = Buffer.from(
+    Array.from({ length: 100 }, (_, i) => (i * 131 + 7) & 0xff)
+  );
+  for (let start = 0; start < 9; start++) {
+    for (let end = start; end <= data.length; end += 5) {
+      const slice = data.subarray(start, end);
+      expect(softwareCrc32(slice)).toBe(bitwiseCrc32(slice));
+      expect(crc32(slice)).toBe(bitwiseCrc32(slice));
+    }
+  }
+  const head = softwareCrc32(data.subarray(0, 37));
+  expect(softwareCrc32(data.subarray(37), head)).toBe(bitwiseCrc32(data));
+});
+
+// 2016-10-26 19:22:38
+const TIMESTAMP = 0x495a9ad3;
+
+test("dosDateTimeToDate reads local time by default", () => {
+  expect(dosDateTimeToDate(TIMESTAMP)).toEqual(new Date(2016, 9, 26, 19, 22, 38));
+});
+
+test("dosDateTimeToDate applies a utc offset in minutes", () => {
+  expect(dosDateTimeToDate(TIMESTAMP, 0)!.toISOString()).toBe(
+    "2016-10-26T19:22:38.000Z"
+  );
+  expect(dosDateTimeToDate(TIMESTAMP, 120)!.toISOString()).toBe(
+    "2016-10-26T17:22:38.000Z"
+  );
+  expect(dosDateTimeToDate(TIMESTAMP, -300)!.toISOString()).toBe(
+    "2016-10-27T00:22:38.000Z"
+  );
+});
+
+test("dosDateTimeToDate rejects invalid dates", () => {
+  const dosTime = (month: number, day: number, hours = 0, seconds = 0) =>
+    (36 << 25) | (month << 21) | (day << 16) | (hours << 11) | (seconds / 2);
+  expect(dosDateTimeToDate(0)).toBe(undefined);
+  expect(dosDateTimeToDate(dosTime(13, 1))).toBe(undefined);
+  expect(dosDateTimeToDate(dosTime(2, 30))).toBe(undefined);
+  expect(dosDateTimeToDate(dosTime(1, 1, 24))).toBe(undefined);
+  expect(dosDateTimeToDate(dosTime(1, 1, 0, 62))).toBe(undefined);
+  expect(dosDateTimeToDate(dosTime(2, 29), 0)!.toISOString()).toBe(
+    "2016-02-29T00:00:00.000Z"
+  );
+});
@@ -1,3 +1,6 @@
+import * as zlib from "zlib";
+import { IExtendedTime } from "./parsing/file-header-parser.js";
+
 export function groupBy<T>(arr: T[], fn: (item: T) => any) {
   return arr.reduce<Record<string, T[]>>((prev, curr) => {
     const groupKey = fn(curr);
@@ -18,3 +21,90 @@ export function mapValues<T extends Object, S>(
     Object.entries(object).map(([key, value]) => [key, mapper(value)])
   ) as { [key in keyof T]: S };
 }
+
+// slicing-by-8: table k gives the crc of a byte followed by k zero bytes
+const CRC_TABLES = new Int32Array(8 * 256);
+for (let n = 0; n < 256; n++) {
+  let c = n;
+  for (let k = 0; k < 8; k++) {
+    c = c & 1 ? 0xedb88320 ^ (c >>> 1) : c >>> 1;
+  }
+  CRC_TABLES[n] = c;
+}
+for (let i = 256; i < CRC_TABLES.length; i++) {
+  const previous = CRC_TABLES[i - 256]!;
+  CRC_TABLES[i] = (previous >>> 8) ^ CRC_TABLES[previous & 0xff]!;
+}
+
+export function softwareCrc32(data: Uint8Array, crc = 0) {
+  const t = CRC_TABLES;
+  crc = ~crc;
+  let i = 0;
+  for (const end = data.length - 7; i < end; i += 8) {
+    const low =
+      crc ^
+      (data[i]! |
+        (data[i + 1]! << 8) |
+        (data[i + 2]! << 16) |
+        (data[i + 3]! << 24));
+    crc =
+      t[0x700 + (low & 0xff)]! ^
+      t[0x600 + ((low >>> 8) & 0xff)]! ^
+      t[0x500 + ((low >>> 16) & 0xff)]! ^
+      t[0x400 + (low >>> 24)]! ^
+      t[0x300 + data[i + 4]!]! ^
+      t[0x200 + data[i + 5]!]! ^
+      t[0x100 + data[i + 6]!]! ^
+      t[data[i + 7]!]!;
+  }
+  for (; i < data.length; i++) {
+    crc = t[(crc ^ data[i]!) & 0xff]! ^ (crc >>> 8);
+  }
+  return ~crc >>> 0;
+}
+
+// Newer Node versions expose zlib's crc32, which uses CPU instructions
+// where available.
+const nativeCrc32 = (
+  zlib as { crc32?: (data: Uint8Array, value?: number) => number }
+).crc32;
+
+export const crc32: (data: Uint8Array, crc?: number) => number = nativeCrc32
+  ? (data, crc = 0) => nativeCrc32(data, crc)
+  : softwareCrc32;
+
+export function extendedTimeToDate(
+  { timestamp, ticks }: IExtendedTime,
+  utcOffset?: number
+) {
+  const date = dosDateTimeToDate(timestamp, utcOffset);
+  return date && new Date(date.getTime() + Math.floor(ticks / 10_000));
+}
+// DOS times are the local time of the packing system. They are read in the
+// local time zone unless utcOffset gives that system's offset in minutes.
+// Invalid dates, e.g. the zero timestamp, give undefined.
+export function dosDateTimeToDate(timestamp: number, utcOffset?: number) {
+  const year = (timestamp >>> 25) + 1980;
+  const month = (timestamp >>> 21) & 0x0f;
+  const day = (timestamp >>> 16) & 0x1f;
+  const hours = (timestamp >>> 11) & 0x1f;
+  const minutes = (timestamp >>> 5) & 0x3f;
+  const seconds = (timestamp & 0x1f) * 2;
+  const daysInMonth = new Date(Date.UTC(year, month, 0)).getUTCDate();
+  if (
+    month < 1 ||
+    month > 12 ||
+    day < 1 ||
+    day > daysInMonth ||
+    hours > 23 ||
+    minutes > 59 ||
+    seconds > 59
+  ) {
+    return undefined;
+  }
+  if (utcOffset === undefined) {
+    return new Date(year, month - 1, day, hours, minutes, seconds);
+  }
+  const utc = Date.UTC(year, month - 1, day, hours, minutes, seconds);
+  return new Date(utc - utcOffset * 60_000);
+}